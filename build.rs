@@ -0,0 +1,181 @@
+#[path = "tools/overlay.rs"]
+mod overlay;
+#[path = "tools/spec.rs"]
+#[allow(dead_code)]
+mod spec;
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use spec::{
+    BodyDef, BodyVariant, CommandTree, InputField, Operation, ParamDef, ResponseDef, SchemaDef,
+};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SPEC_PATH: &str = "n8n-api.yaml";
+
+fn main() -> Result<()> {
+    println!("cargo:rerun-if-changed={SPEC_PATH}");
+    println!("cargo:rerun-if-changed=tools/spec.rs");
+    println!("cargo:rerun-if-changed=tools/overlay.rs");
+    println!("cargo:rerun-if-changed={}", overlay::OVERLAY_DIR);
+
+    let mut doc = spec::load_spec(Path::new(SPEC_PATH))?;
+    for path in overlay::default_overlays(Path::new(overlay::OVERLAY_DIR))? {
+        println!("cargo:rerun-if-changed={}", path.display());
+        for warning in overlay::apply_overlay_file(&mut doc, &path)? {
+            println!("cargo:warning={}: {warning}", path.display());
+        }
+    }
+    let tree = spec::command_tree_from_spec(&doc)?;
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").context("OUT_DIR missing")?);
+    let out = out_dir.join("command_tree.rs");
+    fs::write(&out, emit_tree(&tree)).with_context(|| format!("write {}", out.display()))?;
+    Ok(())
+}
+
+fn emit_tree(tree: &CommandTree) -> String {
+    let resources: Vec<String> = tree
+        .resources
+        .iter()
+        .map(|res| {
+            format!(
+                "Resource {{ name: {}, aliases: &[{}], ops: &[{}] }}",
+                lit(&res.name),
+                join(res.aliases.iter().map(|alias| lit(alias))),
+                join(res.ops.iter().map(emit_op))
+            )
+        })
+        .collect();
+    format!(
+        "pub static COMMAND_TREE: CommandTree = CommandTree {{ version: {}, base_path: {}, resources: &[{}] }};\n",
+        lit(&tree.version),
+        lit(&tree.base_path),
+        resources.join(",\n")
+    )
+}
+
+fn emit_op(op: &Operation) -> String {
+    format!(
+        "Operation {{ name: {}, aliases: &[{}], hidden: {}, method: {}, path: {}, summary: {}, description: {}, params: &[{}], body: {}, response: {} }}",
+        lit(&op.name),
+        join(op.aliases.iter().map(|alias| lit(alias))),
+        op.hidden,
+        lit(&op.method),
+        lit(&op.path),
+        opt_lit(op.summary.as_deref()),
+        opt_lit(op.description.as_deref()),
+        join(op.params.iter().map(emit_param)),
+        op.body
+            .as_ref()
+            .map(|body| format!("Some({})", emit_body(body)))
+            .unwrap_or_else(|| "None".to_string()),
+        op.response
+            .as_ref()
+            .map(|response| format!("Some({})", emit_response(response)))
+            .unwrap_or_else(|| "None".to_string())
+    )
+}
+
+fn emit_response(response: &ResponseDef) -> String {
+    format!(
+        "ResponseDef {{ status: {}, has_body: {} }}",
+        response.status, response.has_body
+    )
+}
+
+fn emit_param(param: &ParamDef) -> String {
+    format!(
+        "ParamDef {{ name: {}, flag: {}, location: {}, style: {}, explode: {}, required: {}, description: {}, schema: {} }}",
+        lit(&param.name),
+        lit(&param.flag),
+        lit(&param.location),
+        lit(&param.style),
+        param.explode,
+        param.required,
+        opt_lit(param.description.as_deref()),
+        emit_schema(&param.schema)
+    )
+}
+
+fn emit_body(body: &BodyDef) -> String {
+    format!(
+        "BodyDef {{ required: {}, content_type: {}, schema: {}, input_fields: &[{}], discriminator: {}, variants: &[{}] }}",
+        body.required,
+        lit(&body.content_type),
+        emit_schema(&body.schema),
+        join(body.input_fields.iter().map(emit_field)),
+        opt_lit(body.discriminator.as_deref()),
+        join(body.variants.iter().map(emit_variant))
+    )
+}
+
+fn emit_variant(variant: &BodyVariant) -> String {
+    format!(
+        "BodyVariant {{ name: {}, discriminator_value: {}, input_fields: &[{}] }}",
+        lit(&variant.name),
+        opt_lit(variant.discriminator_value.as_deref()),
+        join(variant.input_fields.iter().map(emit_field))
+    )
+}
+
+fn emit_field(field: &InputField) -> String {
+    format!(
+        "InputField {{ name: {}, flag: {}, required: {}, read_only: {}, description: {}, schema: {} }}",
+        lit(&field.name),
+        lit(&field.flag),
+        field.required,
+        field.read_only,
+        opt_lit(field.description.as_deref()),
+        emit_schema(&field.schema)
+    )
+}
+
+fn emit_schema(schema: &SchemaDef) -> String {
+    format!(
+        "SchemaDef {{ kind: {}, item: {}, example: {}, enum_values: {}, variants: {} }}",
+        lit(&schema.kind),
+        schema
+            .item
+            .as_ref()
+            .map(|item| format!("Some(&{})", emit_schema(item)))
+            .unwrap_or_else(|| "None".to_string()),
+        schema
+            .example
+            .as_ref()
+            .map(|value| format!("Some({})", json_lit(value)))
+            .unwrap_or_else(|| "None".to_string()),
+        schema
+            .enum_values
+            .as_ref()
+            .map(|values| format!("Some(&[{}])", join(values.iter().map(json_lit))))
+            .unwrap_or_else(|| "None".to_string()),
+        schema
+            .variants
+            .as_ref()
+            .map(|variants| format!("Some(&[{}])", join(variants.iter().map(emit_schema))))
+            .unwrap_or_else(|| "None".to_string())
+    )
+}
+
+/// Rust string literal; `Debug` for `str` produces valid escapes.
+fn lit(value: &str) -> String {
+    format!("{value:?}")
+}
+
+fn opt_lit(value: Option<&str>) -> String {
+    match value {
+        Some(value) => format!("Some({})", lit(value)),
+        None => "None".to_string(),
+    }
+}
+
+fn json_lit(value: &Value) -> String {
+    format!("JsonLiteral({})", lit(&value.to_string()))
+}
+
+fn join(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(", ")
+}
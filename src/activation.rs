@@ -0,0 +1,340 @@
+//! Bulk activation: `--selector` and `--name` on `workflow activate` and
+//! `workflow deactivate`, with a state file that `workflow
+//! restore-activation` replays to put the same workflows back.
+
+use crate::client::{self, ApiContext};
+use crate::command_tree::{CommandTree, Operation};
+use crate::{export, given_body_field, glob_match, json_id, write_stdout_line};
+use anyhow::{Context, Result, anyhow};
+use clap::{Arg, ArgAction, Command};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const ACTIVATE: &str = "activate-workflow";
+const DEACTIVATE: &str = "deactivate-workflow";
+
+/// What a bulk run changed, so it can be undone.
+#[derive(Serialize, Deserialize)]
+struct State {
+    action: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selector: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    changed_at: String,
+    workflows: Vec<StateEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StateEntry {
+    id: String,
+    name: String,
+    /// Whether the workflow was active before the run.
+    active: bool,
+}
+
+#[derive(Default)]
+struct Selector {
+    tags: Vec<String>,
+    project: Option<String>,
+    name: Option<String>,
+}
+
+pub fn supports(op: &Operation) -> bool {
+    op.name == ACTIVATE || op.name == DEACTIVATE
+}
+
+pub fn add_selector_args(op_cmd: Command) -> Command {
+    op_cmd
+        .mut_arg("id", |arg| {
+            arg.required(false)
+                .required_unless_present_any(["selector", "name"])
+                .conflicts_with_all(["selector", "name"])
+        })
+        .arg(
+            Arg::new("selector")
+                .long("selector")
+                .value_name("KEY=VALUE,...")
+                .help("Every workflow matching tag=NAME, project=ID|NAME and name=GLOB"),
+        )
+        .arg(
+            Arg::new("name")
+                .long("name")
+                .value_name("GLOB")
+                .help("Every workflow whose name matches, with * and ? wildcards"),
+        )
+        .arg(
+            Arg::new("state-file")
+                .long("state-file")
+                .value_name("PATH")
+                .conflicts_with("id")
+                .help("Where to record changed workflows for restore-activation"),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("id")
+                .help("List the workflows that would change without changing them"),
+        )
+}
+
+pub fn restore_command() -> Command {
+    Command::new("restore-activation")
+        .about("Undo a bulk activate or deactivate from its state file")
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .value_name("PATH")
+                .required(true)
+                .help("State file written by activate or deactivate --selector/--name"),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .action(ArgAction::SetTrue)
+                .help("List the workflows that would change without changing them"),
+        )
+}
+
+/// Runs a selector-based activate or deactivate; `None` when the command
+/// names a single `--id` and takes the normal path.
+pub fn handle_bulk(
+    ctx: &ApiContext,
+    tree: &CommandTree,
+    op: &Operation,
+    matches: &clap::ArgMatches,
+) -> Option<Result<()>> {
+    if !supports(op) || matches.get_one::<String>("id").is_some() {
+        return None;
+    }
+    Some(bulk(ctx, tree, op, matches))
+}
+
+fn bulk(
+    ctx: &ApiContext,
+    tree: &CommandTree,
+    op: &Operation,
+    matches: &clap::ArgMatches,
+) -> Result<()> {
+    if let Some(field) = given_body_field(op, matches) {
+        return Err(anyhow!(
+            "--{} cannot be combined with a selector",
+            field.flag
+        ));
+    }
+    let raw_selector = matches.get_one::<String>("selector");
+    let name_glob = matches.get_one::<String>("name");
+    let mut selector = match raw_selector {
+        Some(raw) => parse_selector(raw)?,
+        None => Selector::default(),
+    };
+    if let Some(glob) = name_glob {
+        if selector.name.is_some() {
+            return Err(anyhow!("give the name glob either in --selector or --name"));
+        }
+        selector.name = Some(glob.clone());
+    }
+
+    let activate = op.name == ACTIVATE;
+    let verb = if activate { "activate" } else { "deactivate" };
+    let targets: Vec<Value> = select(ctx, tree, &selector)?
+        .into_iter()
+        .filter(|w| w["active"].as_bool().unwrap_or(false) != activate)
+        .collect();
+    if targets.is_empty() {
+        return write_stdout_line(&format!("no workflows to {verb}"));
+    }
+    if matches.get_flag("dry-run") {
+        for workflow in &targets {
+            write_stdout_line(&format!(
+                "would {verb}\t{}\t{}",
+                export::workflow_id(workflow)?,
+                workflow["name"].as_str().unwrap_or_default()
+            ))?;
+        }
+        return Ok(());
+    }
+
+    let changed_at = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    let path = match matches.get_one::<String>("state-file") {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(format!(
+            "n8n-{verb}-{}.json",
+            changed_at.replace([':', '-'], "")
+        )),
+    };
+    let mut state = State {
+        action: verb.to_string(),
+        selector: raw_selector.cloned(),
+        name: name_glob.cloned(),
+        changed_at,
+        workflows: Vec::new(),
+    };
+
+    // Keep going past failures so one bad workflow does not leave the rest
+    // of an incident response undone; the state file lists only successes.
+    let mut failures = 0;
+    for workflow in &targets {
+        let id = export::workflow_id(workflow)?;
+        let name = workflow["name"].as_str().unwrap_or_default().to_string();
+        match set_active(ctx, tree, &id, activate) {
+            Ok(()) => {
+                write_stdout_line(&format!("{verb}d\t{id}\t{name}"))?;
+                state.workflows.push(StateEntry {
+                    id,
+                    name,
+                    active: !activate,
+                });
+                // Saved as it goes, so an interrupted run can still be undone.
+                save_state(&path, &state)?;
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                failures += 1;
+            }
+        }
+    }
+    if !state.workflows.is_empty() {
+        write_stdout_line(&format!(
+            "{verb}d {} workflows; state saved to {}",
+            state.workflows.len(),
+            path.display()
+        ))?;
+    }
+    if failures > 0 {
+        return Err(anyhow!("{failures} workflows could not be {verb}d"));
+    }
+    Ok(())
+}
+
+pub fn handle_restore(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let path = Path::new(
+        matches
+            .get_one::<String>("from")
+            .ok_or_else(|| anyhow!("--from required"))?,
+    );
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let state: State = serde_json::from_str(&raw)
+        .map_err(|err| anyhow!("invalid state file {}: {err}", path.display()))?;
+    let ctx = ApiContext::from_env()?;
+
+    let mut restored = 0;
+    let mut failures = 0;
+    for entry in &state.workflows {
+        let verb = if entry.active {
+            "activate"
+        } else {
+            "deactivate"
+        };
+        if matches.get_flag("dry-run") {
+            write_stdout_line(&format!("would {verb}\t{}\t{}", entry.id, entry.name))?;
+            continue;
+        }
+        match set_active(&ctx, tree, &entry.id, entry.active) {
+            Ok(()) => {
+                write_stdout_line(&format!("{verb}d\t{}\t{}", entry.id, entry.name))?;
+                restored += 1;
+            }
+            Err(err) => {
+                eprintln!("error: {err}");
+                failures += 1;
+            }
+        }
+    }
+    if matches.get_flag("dry-run") {
+        return Ok(());
+    }
+    write_stdout_line(&format!("restored {restored} workflows"))?;
+    if failures > 0 {
+        return Err(anyhow!("{failures} workflows could not be restored"));
+    }
+    Ok(())
+}
+
+fn set_active(ctx: &ApiContext, tree: &CommandTree, id: &str, active: bool) -> Result<()> {
+    let op = if active { ACTIVATE } else { DEACTIVATE };
+    client::call_ok(
+        ctx,
+        tree,
+        "workflow",
+        op,
+        &["--id".to_string(), id.to_string()],
+    )?;
+    Ok(())
+}
+
+fn save_state(path: &Path, state: &State) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(state)? + "\n")
+        .with_context(|| format!("write {}", path.display()))
+}
+
+fn parse_selector(raw: &str) -> Result<Selector> {
+    let mut selector = Selector::default();
+    for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (key, value) = part
+            .split_once('=')
+            .ok_or_else(|| anyhow!("selector {part:?}: expected KEY=VALUE"))?;
+        let value = value.trim().to_string();
+        match key.trim() {
+            "tag" => selector.tags.push(value),
+            "project" => selector.project = Some(value),
+            "name" => selector.name = Some(value),
+            other => {
+                return Err(anyhow!(
+                    "unknown selector key {other:?}; use tag, project or name"
+                ));
+            }
+        }
+    }
+    Ok(selector)
+}
+
+/// Non-archived workflows matching every part of the selector. Tags and
+/// project are filtered by the API, the name glob locally.
+fn select(ctx: &ApiContext, tree: &CommandTree, selector: &Selector) -> Result<Vec<Value>> {
+    let mut args = Vec::new();
+    if !selector.tags.is_empty() {
+        args.extend(["--tags".to_string(), selector.tags.join(",")]);
+    }
+    if let Some(project) = &selector.project {
+        args.extend(["--project-id".to_string(), project_id(ctx, tree, project)?]);
+    }
+    let workflows = client::fetch_all(ctx, tree, "workflow", "get-workflows", &args)?;
+    Ok(workflows
+        .into_iter()
+        .filter(|w| w["isArchived"].as_bool() != Some(true))
+        .filter(|w| {
+            selector
+                .name
+                .as_ref()
+                .is_none_or(|glob| glob_match(glob, w["name"].as_str().unwrap_or_default()))
+        })
+        .collect())
+}
+
+/// A project given by ID or by its unique name.
+fn project_id(ctx: &ApiContext, tree: &CommandTree, project: &str) -> Result<String> {
+    let projects = client::fetch_all(ctx, tree, "projects", "get-projects", &[])?;
+    if projects
+        .iter()
+        .any(|p| json_id(p).is_some_and(|id| id == project))
+    {
+        return Ok(project.to_string());
+    }
+    let mut named = projects
+        .iter()
+        .filter(|p| p["name"].as_str() == Some(project));
+    match (named.next(), named.next()) {
+        (Some(only), None) => {
+            json_id(only).ok_or_else(|| anyhow!("project {project:?} without an id"))
+        }
+        (Some(_), Some(_)) => Err(anyhow!(
+            "several projects are named {project:?}; use its ID"
+        )),
+        (None, _) => Err(anyhow!("no project with ID or name {project:?}")),
+    }
+}
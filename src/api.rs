@@ -0,0 +1,142 @@
+use crate::client::ApiContext;
+use crate::command_tree::CommandTree;
+use crate::{api_url, print_json, send_request};
+use anyhow::{Context, Result, anyhow};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{Value, json};
+use std::fs;
+
+pub fn api_command() -> Command {
+    Command::new("api")
+        .about("Call any API endpoint, including ones missing from the command tree")
+        .arg(
+            Arg::new("method")
+                .required(true)
+                .value_name("METHOD")
+                .help("HTTP method, e.g. GET or POST"),
+        )
+        .arg(
+            Arg::new("path")
+                .required(true)
+                .value_name("PATH")
+                .help("Path below the API base path, e.g. /workflows"),
+        )
+        .arg(
+            Arg::new("query")
+                .long("query")
+                .short('q')
+                .value_name("KEY=VALUE")
+                .action(ArgAction::Append)
+                .help("Query parameter; repeat for more"),
+        )
+        .arg(
+            Arg::new("body")
+                .long("body")
+                .value_name("JSON")
+                .conflicts_with("body-file")
+                .help("Raw JSON request body"),
+        )
+        .arg(
+            Arg::new("body-file")
+                .long("body-file")
+                .value_name("PATH")
+                .help("Path to JSON request body"),
+        )
+        .arg(
+            Arg::new("paginate")
+                .long("paginate")
+                .action(ArgAction::SetTrue)
+                .help("Follow nextCursor and merge every page's data"),
+        )
+}
+
+pub fn handle_api(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let ctx = ApiContext::from_env()?;
+    let pretty = matches.get_flag("pretty");
+    let raw = matches.get_flag("raw");
+    let paginate = matches.get_flag("paginate");
+    if raw && paginate {
+        return Err(anyhow!("--raw cannot be combined with --paginate"));
+    }
+
+    let method = matches
+        .get_one::<String>("method")
+        .ok_or_else(|| anyhow!("method required"))?
+        .to_ascii_uppercase();
+    let path = matches
+        .get_one::<String>("path")
+        .ok_or_else(|| anyhow!("path required"))?;
+
+    // Accept paths copied with the base path (`/api/v1/workflows`) too.
+    let base_path = format!("/{}", tree.base_path.trim().trim_matches('/'));
+    let path = path.strip_prefix(&base_path).unwrap_or(path);
+    let path = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{path}")
+    };
+
+    let mut url = api_url(&ctx.base_url, tree.base_path, &path)?;
+    if let Some(pairs) = matches.get_many::<String>("query") {
+        let mut query = url.query_pairs_mut();
+        for pair in pairs {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| anyhow!("--query expects KEY=VALUE, got {pair}"))?;
+            query.append_pair(key, value);
+        }
+    }
+
+    let body = if let Some(raw_body) = matches.get_one::<String>("body") {
+        Some(serde_json::from_str::<Value>(raw_body).context("invalid JSON body")?)
+    } else if let Some(file) = matches.get_one::<String>("body-file") {
+        let contents =
+            fs::read_to_string(file).with_context(|| format!("failed to read body file {file}"))?;
+        Some(serde_json::from_str::<Value>(&contents).context("invalid JSON body file")?)
+    } else {
+        None
+    };
+
+    if !paginate {
+        let response = send_request(&ctx.api_key, &method, url, body)?;
+        let output = if raw { response.raw } else { response.body };
+        print_json(&output, pretty)?;
+        if !response.ok {
+            return Err(anyhow!("http error: {}", response.status));
+        }
+        return Ok(());
+    }
+
+    let mut items = Vec::new();
+    loop {
+        let response = send_request(&ctx.api_key, &method, url.clone(), body.clone())?;
+        if !response.ok {
+            print_json(&response.body, pretty)?;
+            return Err(anyhow!("http error: {}", response.status));
+        }
+        match response.body.get("data").and_then(Value::as_array) {
+            Some(data) => items.extend(data.iter().cloned()),
+            None => return Err(anyhow!("--paginate needs a response with a data array")),
+        }
+
+        let Some(cursor) = response
+            .body
+            .get("nextCursor")
+            .and_then(Value::as_str)
+            .filter(|c| !c.is_empty())
+        else {
+            break;
+        };
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .filter(|(key, _)| key != "cursor")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+        url.query_pairs_mut()
+            .clear()
+            .extend_pairs(pairs)
+            .append_pair("cursor", cursor);
+    }
+
+    print_json(&json!({ "data": items, "nextCursor": null }), pretty)
+}
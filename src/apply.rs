@@ -0,0 +1,468 @@
+//! `n8n workflow apply`: declarative create-or-update from local workflow
+//! files. Prints a plan and only changes the instance with `--yes`.
+
+use crate::client::{self, ApiContext};
+use crate::command_tree::CommandTree;
+use crate::export::{self, Manifest, ManifestEntry};
+use crate::{edit, find_op, write_stdout_line};
+use anyhow::{Result, anyhow};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+pub fn apply_command() -> Command {
+    Command::new("apply")
+        .about("Create or update workflows to match local files")
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("PATH")
+                .required(true)
+                .help("Workflow file, or a directory of them as written by export"),
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .action(ArgAction::SetTrue)
+                .help("Execute the plan instead of only printing it"),
+        )
+        .arg(
+            Arg::new("sync-tags")
+                .long("sync-tags")
+                .action(ArgAction::SetTrue)
+                .help("Also set each workflow's tags by name, creating missing tags"),
+        )
+        .arg(
+            Arg::new("sync-activation")
+                .long("sync-activation")
+                .action(ArgAction::SetTrue)
+                .help("Also activate or deactivate workflows to match their files"),
+        )
+        .arg(
+            Arg::new("include-static-data")
+                .long("include-static-data")
+                .action(ArgAction::SetTrue)
+                .help("Also send staticData, which n8n updates at runtime"),
+        )
+        .arg(
+            Arg::new("delete-orphans")
+                .long("delete-orphans")
+                .action(ArgAction::SetTrue)
+                .help(
+                    "Delete workflows the directory's manifest lists but no file matches anymore",
+                ),
+        )
+}
+
+struct Local {
+    file: String,
+    workflow: Value,
+}
+
+enum Action {
+    Create,
+    Update,
+    Unchanged,
+}
+
+struct Step {
+    local: Local,
+    action: Action,
+    remote_id: Option<String>,
+    /// Changed fields, including `tags` and `active` when those are synced.
+    changes: Vec<String>,
+}
+
+struct Options {
+    sync_tags: bool,
+    sync_activation: bool,
+}
+
+pub fn handle_apply(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let ctx = ApiContext::from_env()?;
+    let path = PathBuf::from(
+        matches
+            .get_one::<String>("file")
+            .ok_or_else(|| anyhow!("--file required"))?,
+    );
+    let options = Options {
+        sync_tags: matches.get_flag("sync-tags"),
+        sync_activation: matches.get_flag("sync-activation"),
+    };
+    let delete_orphans = matches.get_flag("delete-orphans");
+
+    let (dir, paths) = if path.is_dir() {
+        (Some(path.as_path()), export::workflow_files(&path)?)
+    } else {
+        (None, vec![path.clone()])
+    };
+    let mut manifest = match dir {
+        Some(dir) => Manifest::load(dir)?,
+        None => None,
+    };
+    if delete_orphans && manifest.is_none() {
+        return Err(anyhow!(
+            "--delete-orphans needs a directory with the {} written by workflow export",
+            export::MANIFEST_FILE
+        ));
+    }
+    let locals = paths
+        .iter()
+        .map(|path| {
+            Ok(Local {
+                file: file_name(path),
+                workflow: export::read_workflow_file(path)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let remote = client::fetch_all(&ctx, tree, "workflow", "get-workflows", &[])?;
+    let writable = writable_fields(tree, matches.get_flag("include-static-data"))?;
+    let (steps, orphans) = plan(locals, &remote, manifest.as_ref(), &writable, &options)?;
+    print_plan(&steps, &orphans, delete_orphans)?;
+
+    let pending = steps
+        .iter()
+        .filter(|s| !matches!(s.action, Action::Unchanged))
+        .count();
+    let deletions = if delete_orphans { orphans.len() } else { 0 };
+    if pending + deletions == 0 {
+        return Ok(());
+    }
+    if !matches.get_flag("yes") {
+        return write_stdout_line("run again with --yes to apply");
+    }
+
+    let mut tags = TagIndex::default();
+    for step in &steps {
+        let id = match step.action {
+            Action::Unchanged => continue,
+            Action::Create => {
+                let body = writable_body(&step.local.workflow, &writable);
+                let created = call_with_body(&ctx, tree, "create-workflow", &[], &body)?;
+                export::workflow_id(&created)?
+            }
+            Action::Update => {
+                let id = step.remote_id.clone().unwrap_or_default();
+                if step.changes.iter().any(|c| writable.contains(c)) {
+                    let body = writable_body(&step.local.workflow, &writable);
+                    call_with_body(&ctx, tree, "update-workflow", &["--id", &id], &body)?;
+                }
+                id
+            }
+        };
+        let changed = |field: &str| {
+            matches!(step.action, Action::Create) || step.changes.iter().any(|c| c == field)
+        };
+        if options.sync_tags && changed("tags") {
+            let names = tag_names(&step.local.workflow);
+            let body = tags.resolve(&ctx, tree, &names)?;
+            call_with_body(&ctx, tree, "update-workflow-tags", &["--id", &id], &body)?;
+        }
+        if options.sync_activation
+            && changed("active")
+            && let Some(active) = step.local.workflow.get("active").and_then(Value::as_bool)
+        {
+            let op = if active {
+                "activate-workflow"
+            } else {
+                "deactivate-workflow"
+            };
+            client::call_ok(
+                &ctx,
+                tree,
+                "workflow",
+                op,
+                &["--id".to_string(), id.clone()],
+            )?;
+        }
+        if let Some(manifest) = manifest.as_mut() {
+            let name = step.local.workflow["name"].as_str().unwrap_or_default();
+            manifest.workflows.insert(
+                step.local.file.clone(),
+                ManifestEntry {
+                    id,
+                    name: name.to_string(),
+                },
+            );
+        }
+    }
+    if delete_orphans {
+        for orphan in &orphans {
+            let id = export::workflow_id(orphan)?;
+            client::call_ok(
+                &ctx,
+                tree,
+                "workflow",
+                "delete-workflow",
+                &["--id".to_string(), id],
+            )?;
+        }
+    }
+    if let (Some(dir), Some(mut manifest)) = (dir, manifest) {
+        // Entries for files that were deleted locally are stale, unless they
+        // still name an orphan left for a later --delete-orphans.
+        manifest.workflows.retain(|file, entry| {
+            steps.iter().any(|s| s.local.file == *file)
+                || (!delete_orphans
+                    && orphans
+                        .iter()
+                        .any(|o| export::workflow_id(o).is_ok_and(|id| id == entry.id)))
+        });
+        manifest.save(dir)?;
+    }
+    write_stdout_line(&format!(
+        "applied {pending} changes, deleted {deletions} workflows"
+    ))
+}
+
+/// Pairs every file with a remote workflow, by the file's `id`, then its
+/// manifest entry, then a unique name match, and works out what differs.
+/// Returns the steps and the orphans: remote workflows the manifest lists
+/// that no file claims anymore. Workflows the manifest never listed, such
+/// as those outside a filtered export, are never orphans.
+fn plan(
+    locals: Vec<Local>,
+    remote: &[Value],
+    manifest: Option<&Manifest>,
+    writable: &[String],
+    options: &Options,
+) -> Result<(Vec<Step>, Vec<Value>)> {
+    let by_id: HashMap<String, &Value> = remote
+        .iter()
+        .filter_map(|w| Some((export::workflow_id(w).ok()?, w)))
+        .collect();
+    let mut claimed: HashMap<String, String> = HashMap::new();
+    let mut steps = Vec::new();
+
+    for local in locals {
+        let manifest_id = manifest
+            .and_then(|m| m.workflows.get(&local.file))
+            .map(|entry| entry.id.clone());
+        let name = local.workflow.get("name").and_then(Value::as_str);
+        let remote_id = export::workflow_id(&local.workflow)
+            .ok()
+            .filter(|id| by_id.contains_key(id))
+            .or(manifest_id.filter(|id| by_id.contains_key(id)))
+            .or_else(|| {
+                let mut named = remote
+                    .iter()
+                    .filter(|w| name.is_some() && w.get("name").and_then(Value::as_str) == name);
+                match (named.next(), named.next()) {
+                    (Some(only), None) => export::workflow_id(only)
+                        .ok()
+                        .filter(|id| !claimed.contains_key(id)),
+                    _ => None,
+                }
+            });
+
+        let Some(remote_id) = remote_id else {
+            steps.push(Step {
+                local,
+                action: Action::Create,
+                remote_id: None,
+                changes: Vec::new(),
+            });
+            continue;
+        };
+        if let Some(other) = claimed.insert(remote_id.clone(), local.file.clone()) {
+            return Err(anyhow!(
+                "{} and {other} both match workflow {remote_id}",
+                local.file
+            ));
+        }
+
+        let current = by_id[&remote_id];
+        let changes = changed_fields(&local.workflow, current, writable, options);
+        let action = if changes.is_empty() {
+            Action::Unchanged
+        } else {
+            Action::Update
+        };
+        steps.push(Step {
+            local,
+            action,
+            remote_id: Some(remote_id),
+            changes,
+        });
+    }
+
+    let listed: HashSet<&str> = manifest
+        .into_iter()
+        .flat_map(|m| m.workflows.values())
+        .map(|entry| entry.id.as_str())
+        .collect();
+    let orphans = remote
+        .iter()
+        .filter(|w| w.get("isArchived").and_then(Value::as_bool) != Some(true))
+        .filter(|w| {
+            export::workflow_id(w)
+                .is_ok_and(|id| listed.contains(id.as_str()) && !claimed.contains_key(&id))
+        })
+        .cloned()
+        .collect();
+    Ok((steps, orphans))
+}
+
+/// Fields the file sets that differ remotely; fields the file leaves out
+/// are not managed by it.
+fn changed_fields(
+    local: &Value,
+    remote: &Value,
+    writable: &[String],
+    options: &Options,
+) -> Vec<String> {
+    let mut changes: Vec<String> = writable
+        .iter()
+        .filter(|field| {
+            local
+                .get(field.as_str())
+                .is_some_and(|value| remote.get(field.as_str()) != Some(value))
+        })
+        .cloned()
+        .collect();
+    if options.sync_tags && local.get("tags").is_some() && tag_names(local) != tag_names(remote) {
+        changes.push("tags".to_string());
+    }
+    if options.sync_activation
+        && let Some(active) = local.get("active").and_then(Value::as_bool)
+        && remote.get("active").and_then(Value::as_bool) != Some(active)
+    {
+        changes.push("active".to_string());
+    }
+    changes
+}
+
+fn print_plan(steps: &[Step], orphans: &[Value], delete_orphans: bool) -> Result<()> {
+    let mut counts = [0; 3];
+    for step in steps {
+        let name = step.local.workflow["name"].as_str().unwrap_or_default();
+        let line = match step.action {
+            Action::Create => {
+                counts[0] += 1;
+                format!("create     {}  {name}", step.local.file)
+            }
+            Action::Update => {
+                counts[1] += 1;
+                format!(
+                    "update     {}  {name} ({}): {}",
+                    step.local.file,
+                    step.remote_id.as_deref().unwrap_or_default(),
+                    step.changes.join(", ")
+                )
+            }
+            Action::Unchanged => {
+                counts[2] += 1;
+                format!("unchanged  {}  {name}", step.local.file)
+            }
+        };
+        write_stdout_line(&line)?;
+    }
+    let verb = if delete_orphans {
+        "delete   "
+    } else {
+        "orphan   "
+    };
+    for orphan in orphans {
+        write_stdout_line(&format!(
+            "{verb}  {}  {}",
+            orphan["id"].as_str().unwrap_or_default(),
+            orphan["name"].as_str().unwrap_or_default()
+        ))?;
+    }
+    write_stdout_line(&format!(
+        "plan: {} to create, {} to update, {} unchanged, {} {}",
+        counts[0],
+        counts[1],
+        counts[2],
+        orphans.len(),
+        if delete_orphans {
+            "to delete"
+        } else {
+            "orphaned"
+        }
+    ))
+}
+
+/// The fields apply manages: what update-workflow accepts, as for edit,
+/// with runtime state only on request.
+fn writable_fields(tree: &CommandTree, static_data: bool) -> Result<Vec<String>> {
+    let op = find_op(tree, "workflow", "update-workflow")
+        .ok_or_else(|| anyhow!("unknown command workflow update-workflow"))?;
+    Ok(edit::editable_fields(op, static_data)
+        .map(|field| field.name.to_string())
+        .collect())
+}
+
+fn writable_body(workflow: &Value, writable: &[String]) -> Value {
+    let fields: Map<String, Value> = writable
+        .iter()
+        .filter_map(|field| Some((field.clone(), workflow.get(field.as_str())?.clone())))
+        .collect();
+    Value::Object(fields)
+}
+
+fn call_with_body(
+    ctx: &ApiContext,
+    tree: &CommandTree,
+    op: &str,
+    args: &[&str],
+    body: &Value,
+) -> Result<Value> {
+    let mut args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    args.extend(["--body".to_string(), serde_json::to_string(body)?]);
+    client::call_ok(ctx, tree, "workflow", op, &args)
+}
+
+fn tag_names(workflow: &Value) -> BTreeSet<String> {
+    workflow["tags"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| tag.get("name").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Tag IDs by name, fetched on first use; missing tags are created.
+#[derive(Default)]
+struct TagIndex {
+    ids: Option<HashMap<String, Value>>,
+}
+
+impl TagIndex {
+    fn resolve(
+        &mut self,
+        ctx: &ApiContext,
+        tree: &CommandTree,
+        names: &BTreeSet<String>,
+    ) -> Result<Value> {
+        if self.ids.is_none() {
+            let tags = client::fetch_all(ctx, tree, "tags", "get-tags", &[])?;
+            self.ids = Some(
+                tags.into_iter()
+                    .filter_map(|t| Some((t.get("name")?.as_str()?.to_string(), t["id"].clone())))
+                    .collect(),
+            );
+        }
+        let ids = self.ids.get_or_insert_with(HashMap::new);
+        let mut body = Vec::new();
+        for name in names {
+            if !ids.contains_key(name) {
+                let args = ["--input-name".to_string(), name.clone()];
+                let created = client::call_ok(ctx, tree, "tags", "create-tag", &args)?;
+                ids.insert(name.clone(), created["id"].clone());
+            }
+            body.push(serde_json::json!({ "id": ids[name] }));
+        }
+        Ok(Value::Array(body))
+    }
+}
@@ -0,0 +1,253 @@
+//! Record/replay of HTTP exchanges for offline tests. `--record DIR` saves
+//! every response; `--replay DIR` serves them back without touching the
+//! network and fails on any request that was not recorded.
+//!
+//! Requests are keyed by method, path, sorted query and a hash of the body.
+//! Repeating the same request records `-2`, `-3`, ... variants, which
+//! replay serves in the same order. Repeats are counted per invocation
+//! unless `N8N_CASSETTE_SESSION` names a session, whose counts are kept in
+//! a temp file so a script's separate invocations number as one run.
+//! Recording a key's first occurrence drops its older repeats, so a
+//! re-recording never replays stale responses.
+
+use anyhow::{Context, Result, anyhow};
+use reqwest::Url;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Names a record or replay session spanning several invocations.
+const SESSION_ENV: &str = "N8N_CASSETTE_SESSION";
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+static COUNTS: Mutex<Option<HashMap<String, usize>>> = Mutex::new(None);
+
+enum Mode {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cassette {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    body_sha256: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+pub struct Replayed {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: String,
+}
+
+pub fn init(record: Option<PathBuf>, replay: Option<PathBuf>) {
+    let mode = match (record, replay) {
+        (Some(dir), _) => Mode::Record(dir),
+        (None, Some(dir)) => Mode::Replay(dir),
+        (None, None) => return,
+    };
+    let _ = MODE.set(mode);
+}
+
+pub fn replaying() -> bool {
+    matches!(MODE.get(), Some(Mode::Replay(_)))
+}
+
+/// The recorded response for a request in replay mode, `None` otherwise.
+pub fn replay(method: &str, url: &Url, body: Option<&[u8]>) -> Result<Option<Replayed>> {
+    let Some(Mode::Replay(dir)) = MODE.get() else {
+        return Ok(None);
+    };
+    let request = recorded_request(method, url, body);
+    let key = cassette_key(&request);
+    let count = next_occurrence("replay", dir, &key)?;
+
+    // Past the last recorded repeat, keep serving the final one.
+    let path = (1..=count)
+        .rev()
+        .map(|n| cassette_path(dir, &request, &key, n))
+        .find(|path| path.exists())
+        .ok_or_else(|| {
+            anyhow!(
+                "no recorded response for {method} {} in {}",
+                display_target(&request),
+                dir.display()
+            )
+        })?;
+    let raw = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+    let cassette: Cassette =
+        serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &cassette.response.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            headers.append(name, value);
+        }
+    }
+    Ok(Some(Replayed {
+        status: cassette.response.status,
+        headers,
+        body: cassette.response.body,
+    }))
+}
+
+/// Saves a live response when recording.
+pub fn record(
+    method: &str,
+    url: &Url,
+    body: Option<&[u8]>,
+    status: u16,
+    headers: &HeaderMap,
+    response_body: &str,
+) -> Result<()> {
+    let Some(Mode::Record(dir)) = MODE.get() else {
+        return Ok(());
+    };
+    let request = recorded_request(method, url, body);
+    let key = cassette_key(&request);
+    let occurrence = next_occurrence("record", dir, &key)?;
+    if occurrence == 1 {
+        // Repeats left by an earlier recording would replay after this one.
+        for path in (2..)
+            .map(|n| cassette_path(dir, &request, &key, n))
+            .take_while(|path| path.exists())
+        {
+            fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+        }
+    }
+    let path = cassette_path(dir, &request, &key, occurrence);
+
+    let cassette = Cassette {
+        request,
+        response: RecordedResponse {
+            status,
+            headers: headers
+                .iter()
+                .filter(|(name, _)| *name == reqwest::header::CONTENT_TYPE)
+                .map(|(name, value)| (name.to_string(), value.to_str().unwrap_or("").to_string()))
+                .collect(),
+            body: response_body.to_string(),
+        },
+    };
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    fs::write(&path, serde_json::to_string_pretty(&cassette)? + "\n")
+        .with_context(|| format!("write {}", path.display()))
+}
+
+fn recorded_request(method: &str, url: &Url, body: Option<&[u8]>) -> RecordedRequest {
+    let mut query: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    query.sort();
+    RecordedRequest {
+        method: method.to_uppercase(),
+        path: url.path().to_string(),
+        query,
+        body_sha256: body.map(sha256_hex),
+    }
+}
+
+fn cassette_key(request: &RecordedRequest) -> String {
+    format!(
+        "{} {} {}",
+        display_target(request),
+        request.method,
+        request.body_sha256.as_deref().unwrap_or("-")
+    )
+}
+
+fn display_target(request: &RecordedRequest) -> String {
+    if request.query.is_empty() {
+        return request.path.clone();
+    }
+    let query: Vec<String> = request
+        .query
+        .iter()
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect();
+    format!("{}?{}", request.path, query.join("&"))
+}
+
+/// `GET-api-v1-workflows-<hash>.json`, readable in a directory listing but
+/// unique per key; repeats get a `-N` suffix.
+fn cassette_path(dir: &Path, request: &RecordedRequest, key: &str, occurrence: usize) -> PathBuf {
+    let slug: String = request
+        .path
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.trim_matches('-');
+    let hash = &sha256_hex(key.as_bytes())[..12];
+    let suffix = if occurrence > 1 {
+        format!("-{occurrence}")
+    } else {
+        String::new()
+    };
+    dir.join(format!("{}-{slug}-{hash}{suffix}.json", request.method))
+}
+
+/// Counts this request and returns its occurrence: within the invocation,
+/// or across every invocation sharing `N8N_CASSETTE_SESSION`.
+fn next_occurrence(mode: &str, dir: &Path, key: &str) -> Result<usize> {
+    let session = env::var(SESSION_ENV).ok().filter(|s| !s.is_empty());
+    let Some(session) = session else {
+        let mut counts = COUNTS.lock().unwrap_or_else(|e| e.into_inner());
+        let count = counts
+            .get_or_insert_with(HashMap::new)
+            .entry(key.to_string())
+            .or_insert(0);
+        *count += 1;
+        return Ok(*count);
+    };
+
+    let dir_key = fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let id = format!("{mode}\0{}\0{session}", dir_key.display());
+    let path = env::temp_dir().join(format!(
+        "n8n-cassette-{}.json",
+        &sha256_hex(id.as_bytes())[..16]
+    ));
+    let mut counts: BTreeMap<String, usize> = fs::read_to_string(&path)
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default();
+    let count = counts.entry(key.to_string()).or_insert(0);
+    *count += 1;
+    let count = *count;
+
+    // Written aside and renamed, so a reader never sees a partial file.
+    let tmp = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&tmp, serde_json::to_string(&counts)?)
+        .with_context(|| format!("write {}", tmp.display()))?;
+    fs::rename(&tmp, &path).with_context(|| format!("write {}", path.display()))?;
+    Ok(count)
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
@@ -0,0 +1,121 @@
+use crate::cassette;
+use crate::command_tree::CommandTree;
+use crate::{HttpResponse, build_body, build_op_command, build_url, find_op, send_request};
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+use std::env;
+
+/// Page size used when walking cursor-paginated list operations.
+const PAGE_LIMIT: &str = "250";
+
+/// Stand-in server for `--replay` runs without `N8N_BASE_URL`.
+const REPLAY_BASE_URL: &str = "http://replay.invalid";
+
+pub struct ApiContext {
+    pub api_key: String,
+    pub base_url: String,
+}
+
+impl ApiContext {
+    /// Reads credentials from the environment. Replays never reach a
+    /// server, so there they fall back to placeholders.
+    pub fn from_env() -> Result<Self> {
+        if cassette::replaying() {
+            return Ok(Self {
+                api_key: env::var("N8N_API_KEY").unwrap_or_default(),
+                base_url: env::var("N8N_BASE_URL").unwrap_or_else(|_| REPLAY_BASE_URL.to_string()),
+            });
+        }
+        let api_key = env::var("N8N_API_KEY").context("N8N_API_KEY missing")?;
+        let base_url = env::var("N8N_BASE_URL").context("N8N_BASE_URL missing")?;
+        Ok(Self { api_key, base_url })
+    }
+}
+
+/// Invokes a command-tree operation with CLI-style flags, going through the
+/// same argument parsing, URL and body building as a user invocation.
+pub fn call(
+    ctx: &ApiContext,
+    tree: &CommandTree,
+    resource: &str,
+    op_name: &str,
+    args: &[String],
+) -> Result<HttpResponse> {
+    let op = find_op(tree, resource, op_name)
+        .ok_or_else(|| anyhow!("unknown command {resource} {op_name}"))?;
+    let argv = std::iter::once(op.name.to_string()).chain(args.iter().cloned());
+    let matches = build_op_command(op)
+        .try_get_matches_from(argv)
+        .with_context(|| format!("invalid arguments for {resource} {op_name}"))?;
+
+    let url = build_url(&ctx.base_url, tree.base_path, op, &matches)?;
+    let body = build_body(op, &matches)?;
+    send_request(&ctx.api_key, op.method, url, body)
+}
+
+/// Like [`call`], but turns non-2xx responses into errors and returns the body.
+pub fn call_ok(
+    ctx: &ApiContext,
+    tree: &CommandTree,
+    resource: &str,
+    op_name: &str,
+    args: &[String],
+) -> Result<Value> {
+    let response = call(ctx, tree, resource, op_name, args)?;
+    if !response.ok {
+        return Err(anyhow!(
+            "{resource} {op_name} failed: http error: {}: {}",
+            response.status,
+            error_message(&response.body)
+        ));
+    }
+    Ok(response.body)
+}
+
+/// Collects every item of a cursor-paginated list operation by following
+/// `nextCursor` until the server stops returning one.
+pub fn fetch_all(
+    ctx: &ApiContext,
+    tree: &CommandTree,
+    resource: &str,
+    op_name: &str,
+    args: &[String],
+) -> Result<Vec<Value>> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let mut page_args = args.to_vec();
+        page_args.push("--limit".to_string());
+        page_args.push(PAGE_LIMIT.to_string());
+        if let Some(cursor) = &cursor {
+            page_args.push("--cursor".to_string());
+            page_args.push(cursor.clone());
+        }
+
+        let body = call_ok(ctx, tree, resource, op_name, &page_args)?;
+        if let Some(data) = body.get("data").and_then(Value::as_array) {
+            items.extend(data.iter().cloned());
+        } else if let Some(data) = body.as_array() {
+            items.extend(data.iter().cloned());
+        }
+
+        cursor = body
+            .get("nextCursor")
+            .and_then(Value::as_str)
+            .filter(|c| !c.is_empty())
+            .map(str::to_string);
+        if cursor.is_none() {
+            break;
+        }
+    }
+
+    Ok(items)
+}
+
+pub fn error_message(body: &Value) -> String {
+    match body.get("message").and_then(Value::as_str) {
+        Some(message) => message.to_string(),
+        None => body.to_string(),
+    }
+}
@@ -1,68 +1,142 @@
-use serde::{Deserialize, Serialize};
+use serde::{Serialize, Serializer};
+use serde_json::Value;
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+// The tree is generated from n8n-api.yaml by build.rs into plain statics,
+// so startup never parses JSON or YAML.
+include!(concat!(env!("OUT_DIR"), "/command_tree.rs"));
+
+#[derive(Debug, Serialize)]
 #[allow(dead_code)]
 pub struct CommandTree {
-    pub version: String,
-    pub base_path: String,
-    pub resources: Vec<Resource>,
+    pub version: &'static str,
+    pub base_path: &'static str,
+    pub resources: &'static [Resource],
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Serialize)]
 #[allow(dead_code)]
 pub struct Resource {
-    pub name: String,
-    pub ops: Vec<Operation>,
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub ops: &'static [Operation],
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Serialize)]
 #[allow(dead_code)]
 pub struct Operation {
-    pub name: String,
-    pub method: String,
-    pub path: String,
-    pub summary: Option<String>,
-    pub description: Option<String>,
-    pub params: Vec<ParamDef>,
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub hidden: bool,
+    pub method: &'static str,
+    pub path: &'static str,
+    pub summary: Option<&'static str>,
+    pub description: Option<&'static str>,
+    pub params: &'static [ParamDef],
     pub body: Option<BodyDef>,
+    pub response: Option<ResponseDef>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+impl Resource {
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+
+    /// Operations shown in listings, help and exports; hidden ones stay callable.
+    pub fn visible_ops(&self) -> impl Iterator<Item = &'static Operation> {
+        self.ops.iter().filter(|op| !op.hidden)
+    }
+}
+
+impl Operation {
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+
+    /// Body fields the operation accepts, leaving out the spec's read-only ones.
+    pub fn writable_fields(&self) -> impl Iterator<Item = &'static InputField> + '_ {
+        self.body
+            .iter()
+            .flat_map(|body| body.input_fields)
+            .filter(|field| !field.read_only)
+    }
+}
+
+#[derive(Debug, Serialize)]
 #[allow(dead_code)]
 pub struct ParamDef {
-    pub name: String,
-    pub flag: String,
-    pub location: String,
+    pub name: &'static str,
+    pub flag: &'static str,
+    pub location: &'static str,
+    pub style: &'static str,
+    pub explode: bool,
     pub required: bool,
+    pub description: Option<&'static str>,
     pub schema: SchemaDef,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Serialize)]
 #[allow(dead_code)]
 pub struct BodyDef {
     pub required: bool,
-    pub content_type: String,
+    pub content_type: &'static str,
     pub schema: SchemaDef,
-    pub input_fields: Vec<InputField>,
+    pub input_fields: &'static [InputField],
+    pub discriminator: Option<&'static str>,
+    pub variants: &'static [BodyVariant],
+}
+
+#[derive(Debug, Serialize)]
+#[allow(dead_code)]
+pub struct ResponseDef {
+    pub status: u16,
+    pub has_body: bool,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Serialize)]
+#[allow(dead_code)]
+pub struct BodyVariant {
+    pub name: &'static str,
+    pub discriminator_value: Option<&'static str>,
+    pub input_fields: &'static [InputField],
+}
+
+#[derive(Debug, Serialize)]
 #[allow(dead_code)]
 pub struct InputField {
-    pub name: String,
-    pub flag: String,
+    pub name: &'static str,
+    pub flag: &'static str,
     pub required: bool,
+    pub read_only: bool,
+    pub description: Option<&'static str>,
     pub schema: SchemaDef,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Serialize)]
 #[allow(dead_code)]
 pub struct SchemaDef {
-    pub kind: String,
-    pub item: Option<Box<SchemaDef>>,
+    pub kind: &'static str,
+    pub item: Option<&'static SchemaDef>,
+    pub example: Option<JsonLiteral>,
+    pub enum_values: Option<&'static [JsonLiteral]>,
+    pub variants: Option<&'static [SchemaDef]>,
+}
+
+/// A JSON value embedded as source text; parsed only when it is used.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonLiteral(pub &'static str);
+
+impl JsonLiteral {
+    pub fn value(&self) -> Value {
+        serde_json::from_str(self.0).unwrap_or(Value::Null)
+    }
+}
+
+impl Serialize for JsonLiteral {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value().serialize(serializer)
+    }
 }
 
-pub fn load_command_tree() -> CommandTree {
-    let raw = include_str!("../schemas/command_tree.json");
-    serde_json::from_str(raw).expect("invalid command_tree.json")
+pub fn load_command_tree() -> &'static CommandTree {
+    &COMMAND_TREE
 }
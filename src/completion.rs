@@ -0,0 +1,342 @@
+use crate::client::{self, ApiContext};
+use crate::command_tree::CommandTree;
+use crate::{build_cli, write_stdout_line};
+use anyhow::{Context, Result, anyhow};
+use clap::{Arg, ArgAction, Command};
+use clap_complete::Shell;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// How long fetched IDs stay valid before the instance is queried again.
+const DEFAULT_CACHE_TTL_SECS: u64 = 60;
+
+/// Hidden entry point the dynamic scripts call to fetch candidate IDs.
+pub const COMPLETE_IDS: &str = "__complete-ids";
+
+/// Flags whose values can be completed from the live instance.
+const DYNAMIC_FLAGS: [&str; 3] = ["id", "project-id", "data-table-id"];
+
+pub fn completion_command() -> Command {
+    Command::new("completion")
+        .about("Generate shell completion scripts")
+        .arg(
+            Arg::new("shell")
+                .required(true)
+                .value_parser(["bash", "zsh", "fish"]),
+        )
+        .arg(
+            Arg::new("dynamic")
+                .long("dynamic")
+                .action(ArgAction::SetTrue)
+                .help("Complete --id, --project-id and --data-table-id by querying the instance"),
+        )
+}
+
+pub fn handle_completion(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let shell = matches
+        .get_one::<String>("shell")
+        .ok_or_else(|| anyhow!("shell required"))?;
+    let shell = match shell.as_str() {
+        "bash" => Shell::Bash,
+        "zsh" => Shell::Zsh,
+        "fish" => Shell::Fish,
+        other => return Err(anyhow!("unsupported shell: {other}")),
+    };
+
+    let mut cmd = build_cli(tree);
+    let mut buf = Vec::new();
+    clap_complete::generate(shell, &mut cmd, "n8n", &mut buf);
+    let mut script = String::from_utf8(buf).context("completion script is not UTF-8")?;
+
+    if matches.get_flag("dynamic") {
+        script.push('\n');
+        script.push_str(dynamic_script(shell));
+    }
+
+    write_stdout_line(script.trim_end())
+}
+
+fn dynamic_script(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Zsh => ZSH_DYNAMIC,
+        Shell::Fish => FISH_DYNAMIC,
+        _ => BASH_DYNAMIC,
+    }
+}
+
+const BASH_DYNAMIC: &str = r#"_n8n_dynamic() {
+    local cur prev resource word
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+    case "$prev" in
+        --id|--project-id|--data-table-id)
+            local skip=""
+            for word in "${COMP_WORDS[@]:1}"; do
+                if [[ -n "$skip" ]]; then
+                    skip=""
+                elif [[ "$word" == --har || "$word" == --record || "$word" == --replay ]]; then
+                    skip=1
+                elif [[ "$word" != -* ]]; then
+                    resource="$word"
+                    break
+                fi
+            done
+            local ids
+            ids="$(n8n __complete-ids "$resource" "${prev#--}" 2>/dev/null | cut -f1)"
+            COMPREPLY=( $(compgen -W "$ids" -- "$cur") )
+            return 0
+            ;;
+    esac
+    _n8n "$@"
+}
+
+if [[ "${BASH_VERSINFO[0]}" -eq 4 && "${BASH_VERSINFO[1]}" -ge 4 || "${BASH_VERSINFO[0]}" -gt 4 ]]; then
+    complete -F _n8n_dynamic -o nosort -o bashdefault -o default n8n
+else
+    complete -F _n8n_dynamic -o bashdefault -o default n8n
+fi
+"#;
+
+const ZSH_DYNAMIC: &str = r#"_n8n_dynamic() {
+    local prev="${words[CURRENT-1]}"
+    case "$prev" in
+        --id|--project-id|--data-table-id)
+            local resource word skip
+            for word in "${(@)words[2,-1]}"; do
+                if [[ -n "$skip" ]]; then
+                    skip=""
+                elif [[ "$word" == --har || "$word" == --record || "$word" == --replay ]]; then
+                    skip=1
+                elif [[ "$word" != -* ]]; then
+                    resource="$word"
+                    break
+                fi
+            done
+            local -a ids
+            local line
+            for line in "${(@f)$(n8n __complete-ids "$resource" "${prev#--}" 2>/dev/null)}"; do
+                [[ -z "$line" ]] && continue
+                ids+=("${${line%%$'\t'*}//:/\\:}:${line#*$'\t'}")
+            done
+            _describe -t ids 'id' ids
+            return
+            ;;
+    esac
+    _n8n "$@"
+}
+
+compdef _n8n_dynamic n8n
+"#;
+
+const FISH_DYNAMIC: &str = r#"function __fish_n8n_ids
+    set -l resource
+    set -l skip
+    for tok in (commandline -opc)[2..-1]
+        if test -n "$skip"
+            set skip
+        else if contains -- $tok --har --record --replay
+            set skip 1
+        else if not string match -q -- '-*' $tok
+            set resource $tok
+            break
+        end
+    end
+    n8n __complete-ids "$resource" $argv[1] 2>/dev/null
+end
+
+complete -c n8n -l id -x -a "(__fish_n8n_ids id)"
+complete -c n8n -l project-id -x -a "(__fish_n8n_ids project-id)"
+complete -c n8n -l data-table-id -x -a "(__fish_n8n_ids data-table-id)"
+"#;
+
+/// Prints `id<TAB>label` lines for a dynamic flag. This runs ahead of clap
+/// parsing so it stays out of `--help` and the generated scripts, and its
+/// failures are silent because a completion helper must never spill errors
+/// into the prompt.
+pub fn handle_complete_ids(tree: &CommandTree, args: &[String]) -> Result<()> {
+    let resource = args.first().map(String::as_str).unwrap_or("");
+    let flag = args.get(1).map(String::as_str).unwrap_or("");
+    if !DYNAMIC_FLAGS.contains(&flag) {
+        return Ok(());
+    }
+    // The shell passes the resource as typed, which may be an alias.
+    let resource = tree
+        .resources
+        .iter()
+        .find(|r| r.is_named(resource))
+        .map_or(resource, |r| r.name);
+    let Some(source) = id_source(resource, flag) else {
+        return Ok(());
+    };
+    let Ok(ctx) = ApiContext::from_env() else {
+        return Ok(());
+    };
+
+    let entries = match read_cache(&ctx, source) {
+        Some(entries) => entries,
+        None => {
+            let Ok(entries) = fetch_ids(&ctx, tree, source) else {
+                return Ok(());
+            };
+            write_cache(&ctx, source, &entries);
+            entries
+        }
+    };
+
+    for (id, label) in entries {
+        write_stdout_line(&format!("{id}\t{label}"))?;
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+enum IdSource {
+    Workflows,
+    Credentials,
+    Tags,
+    Projects,
+    DataTables,
+}
+
+impl IdSource {
+    fn cache_key(self) -> &'static str {
+        match self {
+            IdSource::Workflows => "workflows",
+            IdSource::Credentials => "credentials",
+            IdSource::Tags => "tags",
+            IdSource::Projects => "projects",
+            IdSource::DataTables => "data-tables",
+        }
+    }
+}
+
+fn id_source(resource: &str, flag: &str) -> Option<IdSource> {
+    match (flag, resource) {
+        ("project-id", _) => Some(IdSource::Projects),
+        ("data-table-id", _) => Some(IdSource::DataTables),
+        ("id", "workflow") => Some(IdSource::Workflows),
+        ("id", "credential") => Some(IdSource::Credentials),
+        ("id", "tags") => Some(IdSource::Tags),
+        ("id", "projects") => Some(IdSource::Projects),
+        _ => None,
+    }
+}
+
+fn fetch_ids(
+    ctx: &ApiContext,
+    tree: &CommandTree,
+    source: IdSource,
+) -> Result<Vec<(String, String)>> {
+    let items = match source {
+        IdSource::Workflows | IdSource::Credentials => {
+            client::fetch_all(ctx, tree, "workflow", "get-workflows", &[])?
+        }
+        IdSource::Tags => client::fetch_all(ctx, tree, "tags", "get-tags", &[])?,
+        IdSource::Projects => client::fetch_all(ctx, tree, "projects", "get-projects", &[])?,
+        IdSource::DataTables => {
+            client::fetch_all(ctx, tree, "data-table", "list-data-tables", &[])?
+        }
+    };
+
+    if let IdSource::Credentials = source {
+        return Ok(credentials_from_workflows(&items));
+    }
+
+    Ok(items
+        .iter()
+        .filter_map(|item| {
+            let id = id_string(item.get("id")?)?;
+            let label = item
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            Some((id, label))
+        })
+        .collect())
+}
+
+/// The public API has no credential listing, so credentials are discovered
+/// through the `credentials` references on workflow nodes.
+fn credentials_from_workflows(workflows: &[Value]) -> Vec<(String, String)> {
+    let mut found = BTreeMap::new();
+    for workflow in workflows {
+        let nodes = workflow.get("nodes").and_then(Value::as_array);
+        for node in nodes.into_iter().flatten() {
+            let creds = node.get("credentials").and_then(Value::as_object);
+            for cred in creds.into_iter().flat_map(|c| c.values()) {
+                let Some(id) = cred.get("id").and_then(id_string) else {
+                    continue;
+                };
+                let label = cred
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .unwrap_or("")
+                    .to_string();
+                found.entry(id).or_insert(label);
+            }
+        }
+    }
+    found.into_iter().collect()
+}
+
+fn id_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn cache_path(ctx: &ApiContext, source: IdSource) -> Option<PathBuf> {
+    let base = env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    let instance: String = ctx
+        .base_url
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    Some(
+        base.join("n8n-cli")
+            .join("completion")
+            .join(format!("{instance}-{}.json", source.cache_key())),
+    )
+}
+
+fn cache_ttl() -> Duration {
+    let secs = env::var("N8N_COMPLETION_TTL")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_CACHE_TTL_SECS);
+    Duration::from_secs(secs)
+}
+
+fn read_cache(ctx: &ApiContext, source: IdSource) -> Option<Vec<(String, String)>> {
+    let path = cache_path(ctx, source)?;
+    let modified = fs::metadata(&path).ok()?.modified().ok()?;
+    let age = SystemTime::now().duration_since(modified).ok()?;
+    if age > cache_ttl() {
+        return None;
+    }
+    let raw = fs::read_to_string(&path).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+fn write_cache(ctx: &ApiContext, source: IdSource, entries: &[(String, String)]) {
+    let Some(path) = cache_path(ctx, source) else {
+        return;
+    };
+    if let Some(parent) = path.parent()
+        && fs::create_dir_all(parent).is_err()
+    {
+        return;
+    }
+    if let Ok(raw) = serde_json::to_string(entries) {
+        let _ = fs::write(path, raw);
+    }
+}
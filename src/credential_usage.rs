@@ -0,0 +1,226 @@
+//! `n8n credential usage`: which workflows use which credentials, found by
+//! scanning node `credentials` references, since the API cannot list them.
+
+use crate::client::ApiContext;
+use crate::command_tree::CommandTree;
+use crate::{export, search};
+use crate::{json_id, print_json, write_stdout_line};
+use anyhow::{Context, Result, anyhow};
+use clap::{Arg, ArgAction, Command};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+pub fn usage_command() -> Command {
+    Command::new("usage")
+        .about("Map credentials to the workflows that use them")
+        .arg(
+            Arg::new("inventory")
+                .long("inventory")
+                .value_name("PATH")
+                .help(
+                    "JSON or YAML list of existing credentials, to report dangling and unused ones",
+                ),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Emit machine-readable JSON"),
+        )
+}
+
+#[derive(Default, Serialize)]
+struct Report {
+    credentials: Vec<Credential>,
+    /// References by name only, which n8n resolves loosely and export
+    /// tooling cannot follow.
+    name_only_references: Vec<Reference>,
+    /// Referenced IDs absent from the inventory: dangling references, since
+    /// the API cannot list credentials to check them otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unknown: Option<Vec<String>>,
+    /// Inventory entries no workflow references.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unused: Option<Vec<InventoryEntry>>,
+}
+
+#[derive(Serialize)]
+struct Credential {
+    id: String,
+    name: String,
+    #[serde(rename = "type")]
+    credential_type: String,
+    workflows: Vec<WorkflowUse>,
+}
+
+#[derive(Serialize)]
+struct WorkflowUse {
+    id: String,
+    name: String,
+    nodes: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Reference {
+    workflow_id: String,
+    workflow_name: String,
+    node: String,
+    #[serde(rename = "type")]
+    credential_type: String,
+    name: String,
+}
+
+#[derive(Clone, Serialize)]
+struct InventoryEntry {
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+pub fn handle_usage(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let inventory = matches
+        .get_one::<String>("inventory")
+        .map(|path| load_inventory(Path::new(path)))
+        .transpose()?;
+    let ctx = ApiContext::from_env()?;
+    let workflows = search::full_workflows(&ctx, tree)?;
+
+    let mut report = Report::default();
+    let mut by_id: BTreeMap<String, Credential> = BTreeMap::new();
+    for workflow in &workflows {
+        let workflow_id = export::workflow_id(workflow)?;
+        let workflow_name = workflow["name"].as_str().unwrap_or_default();
+        for node in workflow["nodes"].as_array().into_iter().flatten() {
+            let node_name = node["name"].as_str().unwrap_or_default();
+            for (credential_type, reference) in
+                node["credentials"].as_object().into_iter().flatten()
+            {
+                let name = reference["name"].as_str().unwrap_or_default().to_string();
+                let Some(id) = json_id(reference) else {
+                    report.name_only_references.push(Reference {
+                        workflow_id: workflow_id.clone(),
+                        workflow_name: workflow_name.to_string(),
+                        node: node_name.to_string(),
+                        credential_type: credential_type.clone(),
+                        name,
+                    });
+                    continue;
+                };
+                let credential = by_id.entry(id.clone()).or_insert_with(|| Credential {
+                    id,
+                    name,
+                    credential_type: credential_type.clone(),
+                    workflows: Vec::new(),
+                });
+                match credential.workflows.last_mut() {
+                    Some(last) if last.id == workflow_id => last.nodes.push(node_name.to_string()),
+                    _ => credential.workflows.push(WorkflowUse {
+                        id: workflow_id.clone(),
+                        name: workflow_name.to_string(),
+                        nodes: vec![node_name.to_string()],
+                    }),
+                }
+            }
+        }
+    }
+
+    if let Some(inventory) = &inventory {
+        report.unknown = Some(
+            by_id
+                .keys()
+                .filter(|id| !inventory.iter().any(|entry| entry.id == **id))
+                .cloned()
+                .collect(),
+        );
+        report.unused = Some(
+            inventory
+                .iter()
+                .filter(|entry| !by_id.contains_key(&entry.id))
+                .cloned()
+                .collect(),
+        );
+    }
+    report.credentials = by_id.into_values().collect();
+
+    if matches.get_flag("json") {
+        return print_json(&serde_json::to_value(&report)?, matches.get_flag("pretty"));
+    }
+    print_text(&report)
+}
+
+/// Accepts a list of IDs, a list of objects with `id` (and optionally
+/// `name`), or either wrapped in `{"data": [...]}`.
+fn load_inventory(path: &Path) -> Result<Vec<InventoryEntry>> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let value: Value = serde_yaml::from_str(&raw)
+        .map_err(|err| anyhow!("invalid inventory {}: {err}", path.display()))?;
+    let items = value
+        .get("data")
+        .unwrap_or(&value)
+        .as_array()
+        .ok_or_else(|| anyhow!("inventory {} is not a list", path.display()))?;
+    items
+        .iter()
+        .map(|item| {
+            let id = match item {
+                Value::Object(_) => json_id(item),
+                Value::String(id) => Some(id.clone()),
+                Value::Number(id) => Some(id.to_string()),
+                _ => None,
+            }
+            .ok_or_else(|| anyhow!("inventory entry without an id: {item}"))?;
+            let name = item.get("name").and_then(Value::as_str).map(str::to_string);
+            Ok(InventoryEntry { id, name })
+        })
+        .collect()
+}
+
+fn print_text(report: &Report) -> Result<()> {
+    for credential in &report.credentials {
+        write_stdout_line(&format!(
+            "{}\t{}\t{}\tused by {} workflows",
+            credential.id,
+            credential.credential_type,
+            credential.name,
+            credential.workflows.len()
+        ))?;
+        for workflow in &credential.workflows {
+            write_stdout_line(&format!(
+                "    {}\t{}\t{}",
+                workflow.id,
+                workflow.name,
+                workflow.nodes.join(", ")
+            ))?;
+        }
+    }
+    for reference in &report.name_only_references {
+        write_stdout_line(&format!(
+            "name only: workflow {} ({}) node {:?} references {} {:?} without an id",
+            reference.workflow_id,
+            reference.workflow_name,
+            reference.node,
+            reference.credential_type,
+            reference.name
+        ))?;
+    }
+    for id in report.unknown.iter().flatten() {
+        write_stdout_line(&format!(
+            "unknown: credential {id} is referenced but not in the inventory"
+        ))?;
+    }
+    for entry in report.unused.iter().flatten() {
+        let name = entry
+            .name
+            .as_ref()
+            .map(|name| format!(" ({name})"))
+            .unwrap_or_default();
+        write_stdout_line(&format!(
+            "unused: credential {}{name} is not used by any workflow",
+            entry.id
+        ))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,403 @@
+//! `n8n workflow diff`: a semantic diff between a local workflow file and
+//! the live workflow, by node rather than by line.
+
+use crate::client::{self, ApiContext};
+use crate::command_tree::CommandTree;
+use crate::export::{self, RUNTIME_FIELDS, VOLATILE_FIELDS};
+use crate::{print_json, write_stdout_line};
+use anyhow::{Result, anyhow};
+use clap::{Arg, ArgAction, Command};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// Top-level fields that identify the workflow rather than describe it.
+const IDENTITY_FIELDS: &[&str] = &["id", "createdAt"];
+
+/// Longest value shown in text output before it is cut short.
+const MAX_VALUE_WIDTH: usize = 80;
+
+pub fn diff_command() -> Command {
+    Command::new("diff")
+        .about("Show what differs between a local workflow file and the live workflow")
+        .arg(
+            Arg::new("id")
+                .long("id")
+                .value_name("ID")
+                .required(true)
+                .help("Workflow to compare against"),
+        )
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("PATH")
+                .required(true)
+                .help("Local workflow file, JSON or YAML"),
+        )
+        .arg(
+            Arg::new("include-positions")
+                .long("include-positions")
+                .action(ArgAction::SetTrue)
+                .help("Also report nodes that only moved on the canvas"),
+        )
+        .arg(
+            Arg::new("include-volatile")
+                .long("include-volatile")
+                .action(ArgAction::SetTrue)
+                .help("Also compare fields that change on every save, like updatedAt"),
+        )
+        .arg(
+            Arg::new("include-static-data")
+                .long("include-static-data")
+                .action(ArgAction::SetTrue)
+                .help("Also compare staticData, which n8n updates at runtime"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Emit machine-readable JSON"),
+        )
+}
+
+#[derive(Default, Serialize)]
+struct WorkflowDiff {
+    nodes_added: Vec<NodeRef>,
+    nodes_removed: Vec<NodeRef>,
+    nodes_renamed: Vec<Rename>,
+    node_changes: Vec<NodeChange>,
+    connections_added: Vec<String>,
+    connections_removed: Vec<String>,
+    /// Settings and other top-level fields.
+    field_changes: Vec<Change>,
+}
+
+#[derive(Serialize)]
+struct NodeRef {
+    name: String,
+    #[serde(rename = "type")]
+    node_type: String,
+}
+
+#[derive(Serialize)]
+struct Rename {
+    from: String,
+    to: String,
+}
+
+#[derive(Serialize)]
+struct NodeChange {
+    node: String,
+    #[serde(flatten)]
+    change: Change,
+}
+
+/// One value that differs; `remote` is the live workflow, `local` the file.
+#[derive(Serialize)]
+struct Change {
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remote: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    local: Option<Value>,
+}
+
+struct Options {
+    positions: bool,
+    volatile: bool,
+    static_data: bool,
+}
+
+pub fn handle_diff(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let ctx = ApiContext::from_env()?;
+    let id = matches
+        .get_one::<String>("id")
+        .ok_or_else(|| anyhow!("--id required"))?;
+    let file = matches
+        .get_one::<String>("file")
+        .ok_or_else(|| anyhow!("--file required"))?;
+    let options = Options {
+        positions: matches.get_flag("include-positions"),
+        volatile: matches.get_flag("include-volatile"),
+        static_data: matches.get_flag("include-static-data"),
+    };
+
+    let local = export::read_workflow_file(Path::new(file))?;
+    let remote = client::call_ok(
+        &ctx,
+        tree,
+        "workflow",
+        "get-workflow",
+        &["--id".to_string(), id.clone()],
+    )?;
+    let diff = diff_workflows(&remote, &local, &options);
+
+    if matches.get_flag("json") {
+        return print_json(&serde_json::to_value(&diff)?, matches.get_flag("pretty"));
+    }
+    print_text(&diff)
+}
+
+fn diff_workflows(remote: &Value, local: &Value, options: &Options) -> WorkflowDiff {
+    let mut diff = WorkflowDiff::default();
+    let remote_nodes = nodes(remote);
+    let local_nodes = nodes(local);
+
+    // Match nodes by ID first, since that survives a rename, then by name.
+    let mut matched_remote = vec![false; remote_nodes.len()];
+    let mut renames: HashMap<String, String> = HashMap::new();
+    for local_node in &local_nodes {
+        let local_name = node_name(local_node);
+        let found = local_node
+            .get("id")
+            .and_then(|id| {
+                remote_nodes
+                    .iter()
+                    .position(|r| r.get("id") == Some(id))
+                    .filter(|&i| !matched_remote[i])
+            })
+            .or_else(|| {
+                remote_nodes
+                    .iter()
+                    .enumerate()
+                    .position(|(i, r)| !matched_remote[i] && node_name(r) == local_name)
+            });
+        let Some(index) = found else {
+            diff.nodes_added.push(node_ref(local_node));
+            continue;
+        };
+        matched_remote[index] = true;
+        let remote_node = remote_nodes[index];
+        let remote_name = node_name(remote_node);
+        if remote_name != local_name {
+            diff.nodes_renamed.push(Rename {
+                from: remote_name.to_string(),
+                to: local_name.to_string(),
+            });
+            renames.insert(remote_name.to_string(), local_name.to_string());
+        }
+
+        let mut changes = Vec::new();
+        let skip: &[&str] = if options.positions {
+            &["id", "name"]
+        } else {
+            &["id", "name", "position"]
+        };
+        diff_objects("", remote_node, local_node, skip, &mut changes);
+        diff.node_changes
+            .extend(changes.into_iter().map(|change| NodeChange {
+                node: local_name.to_string(),
+                change,
+            }));
+    }
+    for (index, remote_node) in remote_nodes.iter().enumerate() {
+        if !matched_remote[index] {
+            diff.nodes_removed.push(node_ref(remote_node));
+        }
+    }
+
+    // Connections are keyed by node name, so follow renames before comparing.
+    let remote_connections = connections(remote, &renames);
+    let local_connections = connections(local, &HashMap::new());
+    diff.connections_added = local_connections
+        .difference(&remote_connections)
+        .cloned()
+        .collect();
+    diff.connections_removed = remote_connections
+        .difference(&local_connections)
+        .cloned()
+        .collect();
+
+    // Fields the file leaves out are not managed by it, as with apply.
+    let mut skip = vec!["nodes", "connections"];
+    skip.extend(IDENTITY_FIELDS);
+    if !options.volatile {
+        skip.extend(VOLATILE_FIELDS);
+    }
+    if !options.static_data {
+        skip.extend(RUNTIME_FIELDS);
+    }
+    let local_fields = comparable_fields(local);
+    let remote_fields: Map<String, Value> = comparable_fields(remote)
+        .into_iter()
+        .filter(|(key, _)| local_fields.contains_key(key))
+        .collect();
+    diff_objects(
+        "",
+        &Value::Object(remote_fields),
+        &Value::Object(local_fields),
+        &skip,
+        &mut diff.field_changes,
+    );
+    diff
+}
+
+fn nodes(workflow: &Value) -> Vec<&Value> {
+    workflow["nodes"].as_array().into_iter().flatten().collect()
+}
+
+fn node_name(node: &Value) -> &str {
+    node["name"].as_str().unwrap_or_default()
+}
+
+fn node_ref(node: &Value) -> NodeRef {
+    NodeRef {
+        name: node_name(node).to_string(),
+        node_type: node["type"].as_str().unwrap_or_default().to_string(),
+    }
+}
+
+/// Every connection as `Source -> Target`, noting the output or input when
+/// it is not the first `main` one.
+fn connections(workflow: &Value, renames: &HashMap<String, String>) -> BTreeSet<String> {
+    let rename = |name: &str| {
+        renames
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.to_string())
+    };
+    let mut edges = BTreeSet::new();
+    let Some(sources) = workflow["connections"].as_object() else {
+        return edges;
+    };
+    for (source, kinds) in sources {
+        for (kind, outputs) in kinds.as_object().into_iter().flatten() {
+            for (output, targets) in outputs.as_array().into_iter().flatten().enumerate() {
+                for target in targets.as_array().into_iter().flatten() {
+                    let mut from = rename(source);
+                    if kind != "main" || output > 0 {
+                        from.push_str(&format!(" ({kind} {output})"));
+                    }
+                    let mut to = rename(target["node"].as_str().unwrap_or_default());
+                    let input_kind = target["type"].as_str().unwrap_or("main");
+                    let input = target["index"].as_u64().unwrap_or(0);
+                    if input_kind != "main" || input > 0 {
+                        to.push_str(&format!(" ({input_kind} {input})"));
+                    }
+                    edges.insert(format!("{from} -> {to}"));
+                }
+            }
+        }
+    }
+    edges
+}
+
+/// Top-level fields with tags reduced to their sorted names, since tag IDs
+/// differ between instances.
+fn comparable_fields(workflow: &Value) -> Map<String, Value> {
+    let mut fields = workflow.as_object().cloned().unwrap_or_default();
+    if let Some(tags) = fields.get("tags").and_then(Value::as_array) {
+        let names: BTreeSet<&str> = tags
+            .iter()
+            .filter_map(|tag| tag.get("name").and_then(Value::as_str))
+            .collect();
+        let names = names.into_iter().map(|n| Value::String(n.to_string()));
+        fields.insert("tags".to_string(), Value::Array(names.collect()));
+    }
+    fields
+}
+
+fn diff_objects(prefix: &str, remote: &Value, local: &Value, skip: &[&str], out: &mut Vec<Change>) {
+    let empty = Map::new();
+    let remote = remote.as_object().unwrap_or(&empty);
+    let local = local.as_object().unwrap_or(&empty);
+    let keys: BTreeSet<&String> = remote.keys().chain(local.keys()).collect();
+    for key in keys {
+        if skip.contains(&key.as_str()) {
+            continue;
+        }
+        let path = join_path(prefix, key);
+        diff_values(&path, remote.get(key), local.get(key), out);
+    }
+}
+
+/// Recurses into objects and equal-length arrays so each change is reported
+/// at the deepest path where the two sides still line up.
+fn diff_values(path: &str, remote: Option<&Value>, local: Option<&Value>, out: &mut Vec<Change>) {
+    match (remote, local) {
+        (Some(a), Some(b)) if a == b => {}
+        (Some(a @ Value::Object(_)), Some(b @ Value::Object(_))) => {
+            diff_objects(path, a, b, &[], out);
+        }
+        (Some(Value::Array(a)), Some(Value::Array(b))) if a.len() == b.len() => {
+            for (index, (a, b)) in a.iter().zip(b).enumerate() {
+                diff_values(&format!("{path}[{index}]"), Some(a), Some(b), out);
+            }
+        }
+        (None, None) => {}
+        (remote, local) => out.push(Change {
+            path: path.to_string(),
+            remote: remote.cloned(),
+            local: local.cloned(),
+        }),
+    }
+}
+
+/// `a.b` for plain keys, `a["odd key"]` otherwise.
+fn join_path(prefix: &str, key: &str) -> String {
+    let plain = !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    match (prefix.is_empty(), plain) {
+        (true, true) => key.to_string(),
+        (false, true) => format!("{prefix}.{key}"),
+        _ => format!("{prefix}[{}]", Value::String(key.to_string())),
+    }
+}
+
+fn print_text(diff: &WorkflowDiff) -> Result<()> {
+    let mut lines = Vec::new();
+    for node in &diff.nodes_added {
+        lines.push(format!("+ node {} ({})", node.name, node.node_type));
+    }
+    for node in &diff.nodes_removed {
+        lines.push(format!("- node {} ({})", node.name, node.node_type));
+    }
+    for rename in &diff.nodes_renamed {
+        lines.push(format!("~ node {} renamed to {}", rename.from, rename.to));
+    }
+    for change in &diff.node_changes {
+        lines.push(format!(
+            "~ node {}: {}",
+            change.node,
+            describe(&change.change)
+        ));
+    }
+    for edge in &diff.connections_added {
+        lines.push(format!("+ connection {edge}"));
+    }
+    for edge in &diff.connections_removed {
+        lines.push(format!("- connection {edge}"));
+    }
+    for change in &diff.field_changes {
+        lines.push(format!("~ {}", describe(change)));
+    }
+    if lines.is_empty() {
+        return write_stdout_line("no differences");
+    }
+    for line in lines {
+        write_stdout_line(&line)?;
+    }
+    Ok(())
+}
+
+fn describe(change: &Change) -> String {
+    let show = |value: &Option<Value>| match value {
+        Some(value) => shorten(&value.to_string()),
+        None => "(unset)".to_string(),
+    };
+    format!(
+        "{}: {} -> {}",
+        change.path,
+        show(&change.remote),
+        show(&change.local)
+    )
+}
+
+fn shorten(text: &str) -> String {
+    if text.chars().count() <= MAX_VALUE_WIDTH {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(MAX_VALUE_WIDTH - 3).collect();
+    format!("{cut}...")
+}
@@ -0,0 +1,395 @@
+use crate::command_tree::{CommandTree, InputField, Operation, ParamDef, SchemaDef};
+use crate::{find_op, schema_label, write_stdout_line};
+use anyhow::{Result, anyhow};
+use serde_json::{Value, json};
+
+pub fn handle_list(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    if let Some(term) = matches.get_one::<String>("search") {
+        return handle_search(tree, term, matches.get_flag("json"));
+    }
+
+    if matches.get_flag("json") {
+        let mut out = Vec::new();
+        for res in tree.resources {
+            let ops: Vec<String> = res.visible_ops().map(|op| op.name.to_string()).collect();
+            out.push(json!({"resource": res.name, "ops": ops}));
+        }
+        write_stdout_line(&serde_json::to_string_pretty(&out)?)?;
+        return Ok(());
+    }
+
+    for res in tree.resources {
+        write_stdout_line(res.name)?;
+        let rows: Vec<Vec<String>> = res
+            .visible_ops()
+            .map(|op| vec![op.name.to_string(), summary_line(op)])
+            .collect();
+        for line in columns(&rows) {
+            write_stdout_line(&format!("  {line}"))?;
+        }
+    }
+    Ok(())
+}
+
+fn handle_search(tree: &CommandTree, term: &str, as_json: bool) -> Result<()> {
+    let mut hits = Vec::new();
+    for res in tree.resources {
+        for op in res.visible_ops() {
+            if let Some(score) = search_score(res.name, op, term) {
+                hits.push((score, res.name, op));
+            }
+        }
+    }
+    hits.sort_by_key(|hit| std::cmp::Reverse(hit.0));
+
+    if as_json {
+        let out: Vec<Value> = hits
+            .iter()
+            .map(|(score, res, op)| {
+                json!({
+                    "resource": res,
+                    "op": op.name,
+                    "method": op.method,
+                    "path": op.path,
+                    "summary": op.summary,
+                    "score": score,
+                })
+            })
+            .collect();
+        write_stdout_line(&serde_json::to_string_pretty(&out)?)?;
+        return Ok(());
+    }
+
+    if hits.is_empty() {
+        return Err(anyhow!("no operations match {term:?}"));
+    }
+
+    let rows: Vec<Vec<String>> = hits
+        .iter()
+        .map(|(_, res, op)| vec![format!("{res} {}", op.name), summary_line(op)])
+        .collect();
+    for line in columns(&rows) {
+        write_stdout_line(&line)?;
+    }
+    Ok(())
+}
+
+/// Scores an operation against a search term; every whitespace-separated
+/// word must match somewhere. Name hits outrank summary and description hits,
+/// and a word that is only a subsequence of the command name scores lowest.
+fn search_score(resource: &str, op: &Operation, term: &str) -> Option<u32> {
+    let name = std::iter::once(op.name)
+        .chain(op.aliases.iter().copied())
+        .map(|name| format!("{resource} {name}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    let summary = op.summary.unwrap_or("").to_lowercase();
+    let description = op.description.unwrap_or("").to_lowercase();
+
+    let mut total = 0;
+    for word in term.split_whitespace() {
+        let word = word.to_lowercase();
+        let score = if name.contains(&word) {
+            8
+        } else if summary.contains(&word) {
+            4
+        } else if description.contains(&word) {
+            2
+        } else if is_subsequence(&word, &name) {
+            1
+        } else {
+            return None;
+        };
+        total += score;
+    }
+
+    (total > 0).then_some(total)
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|c| chars.any(|h| h == c))
+}
+
+pub fn handle_describe(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let resource = matches
+        .get_one::<String>("resource")
+        .ok_or_else(|| anyhow!("resource required"))?;
+    let op_name = matches
+        .get_one::<String>("op")
+        .ok_or_else(|| anyhow!("operation required"))?;
+
+    let op = find_op(tree, resource, op_name)
+        .ok_or_else(|| anyhow!("unknown command {resource} {op_name}"))?;
+
+    if matches.get_flag("json") {
+        write_stdout_line(&serde_json::to_string_pretty(op)?)?;
+        return Ok(());
+    }
+
+    let resource = tree
+        .resources
+        .iter()
+        .find(|r| r.is_named(resource))
+        .map_or(resource.as_str(), |r| r.name);
+    write_stdout_line(&format!("{} {}", resource, op.name))?;
+    if !op.aliases.is_empty() {
+        write_stdout_line(&format!("  aliases: {}", op.aliases.join(", ")))?;
+    }
+    write_stdout_line(&format!("  method: {}", op.method))?;
+    write_stdout_line(&format!("  path: {}", op.path))?;
+    if let Some(summary) = &op.summary
+        && !summary.trim().is_empty()
+    {
+        write_stdout_line(&format!("  summary: {}", summary.trim()))?;
+    }
+    if let Some(description) = &op.description
+        && !description.trim().is_empty()
+    {
+        write_stdout_line("  description:")?;
+        for line in description.trim().lines() {
+            write_stdout_line(&format!("    {}", line.trim_end()))?;
+        }
+    }
+    if !op.params.is_empty() {
+        write_stdout_line("  params:")?;
+        let rows: Vec<Vec<String>> = op.params.iter().map(param_row).collect();
+        for line in columns(&rows) {
+            write_stdout_line(&format!("    {line}"))?;
+        }
+    }
+    if let Some(body) = &op.body {
+        let required = if body.required { " (required)" } else { "" };
+        write_stdout_line(&format!("  body: {}{required}", body.content_type))?;
+        if !body.input_fields.is_empty() {
+            write_stdout_line("  body fields:")?;
+            let rows: Vec<Vec<String>> = body.input_fields.iter().map(field_row).collect();
+            for line in columns(&rows) {
+                write_stdout_line(&format!("    {line}"))?;
+            }
+        }
+        if !body.variants.is_empty() {
+            write_stdout_line("  body variants (--variant):")?;
+            for variant in body.variants {
+                let tag = match (body.discriminator, variant.discriminator_value) {
+                    (Some(prop), Some(value)) => format!(" ({prop}={value})"),
+                    _ => String::new(),
+                };
+                let required: Vec<String> = variant
+                    .input_fields
+                    .iter()
+                    .filter(|f| f.required)
+                    .map(|f| format!("--{}", f.flag))
+                    .collect();
+                write_stdout_line(&format!(
+                    "    {}{tag}: requires {}",
+                    variant.name,
+                    if required.is_empty() {
+                        "nothing".to_string()
+                    } else {
+                        required.join(" ")
+                    }
+                ))?;
+            }
+        }
+    }
+    write_stdout_line("  examples:")?;
+    for example in example_invocations(resource, op) {
+        write_stdout_line(&format!("    {example}"))?;
+    }
+
+    Ok(())
+}
+
+fn param_row(param: &ParamDef) -> Vec<String> {
+    let marker = if param.required { "*" } else { "" };
+    vec![
+        format!("--{}{marker}", param.flag),
+        schema_label(&param.schema),
+        format!("({})", param.location),
+        param.description.unwrap_or_default().to_string(),
+    ]
+}
+
+fn field_row(field: &InputField) -> Vec<String> {
+    let marker = if field.required { "*" } else { "" };
+    vec![
+        format!("--{}{marker}", field.flag),
+        schema_label(&field.schema),
+        field.description.unwrap_or_default().to_string(),
+    ]
+}
+
+pub fn handle_tree(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    if matches.get_flag("json") {
+        write_stdout_line(&serde_json::to_string_pretty(tree)?)?;
+        return Ok(());
+    }
+
+    write_stdout_line(&format!("n8n (api {}, {})", tree.version, tree.base_path))?;
+    let res_count = tree.resources.len();
+    for (res_idx, res) in tree.resources.iter().enumerate() {
+        let last_res = res_idx + 1 == res_count;
+        let (branch, indent) = if last_res {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        write_stdout_line(&format!("{branch}{}", res.name))?;
+
+        let rows: Vec<Vec<String>> = res
+            .visible_ops()
+            .map(|op| {
+                vec![
+                    op.name.to_string(),
+                    op.method.to_string(),
+                    op.path.to_string(),
+                    summary_line(op),
+                ]
+            })
+            .collect();
+        let op_count = rows.len();
+        for (op_idx, line) in columns(&rows).into_iter().enumerate() {
+            let op_branch = if op_idx + 1 == op_count {
+                "└── "
+            } else {
+                "├── "
+            };
+            write_stdout_line(&format!("{indent}{op_branch}{line}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Builds copy-pasteable invocations for an operation: one with only the
+/// required flags, one with every flag, and one reading the body from a file.
+pub fn example_invocations(resource: &str, op: &Operation) -> Vec<String> {
+    let prefix = format!("n8n {resource} {}", op.name);
+    let mut required = Vec::new();
+    let mut optional = Vec::new();
+
+    for param in op.params {
+        let flag = format!(
+            "--{} {}",
+            param.flag,
+            example_value(param.name, &param.schema)
+        );
+        if param.required {
+            required.push(flag);
+        } else {
+            optional.push(flag);
+        }
+    }
+
+    let mut body_fields = Vec::new();
+    if let Some(body) = &op.body {
+        for field in body.input_fields {
+            let flag = format!(
+                "--{} {}",
+                field.flag,
+                example_value(field.name, &field.schema)
+            );
+            if field.required {
+                body_fields.push(flag);
+            } else {
+                optional.push(flag);
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    let mut minimal = required.clone();
+    minimal.extend(body_fields.iter().cloned());
+    out.push(join_invocation(&prefix, &minimal));
+
+    if !optional.is_empty() {
+        let mut full = minimal.clone();
+        full.extend(optional);
+        out.push(join_invocation(&prefix, &full));
+    }
+
+    if op.body.is_some() {
+        let mut from_file = required;
+        from_file.push("--body-file body.json".to_string());
+        out.push(join_invocation(&prefix, &from_file));
+    }
+
+    out
+}
+
+fn join_invocation(prefix: &str, flags: &[String]) -> String {
+    if flags.is_empty() {
+        return prefix.to_string();
+    }
+    format!("{prefix} {}", flags.join(" "))
+}
+
+fn example_value(name: &str, schema: &SchemaDef) -> String {
+    if let Some(example) = &schema.example {
+        let rendered = match example.value() {
+            Value::String(s) => s,
+            other => other.to_string(),
+        };
+        return shell_quote(&rendered);
+    }
+
+    match schema.kind {
+        "integer" | "number" => "10".to_string(),
+        "boolean" => "true".to_string(),
+        "object" => "'{}'".to_string(),
+        "array" => {
+            let item = schema.item;
+            match item {
+                Some(item) if item.kind != "object" => example_value(name, item),
+                _ => "'[]'".to_string(),
+            }
+        }
+        _ => format!("<{name}>"),
+    }
+}
+
+fn shell_quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.,:/@".contains(c));
+    if safe {
+        return value.to_string();
+    }
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn summary_line(op: &Operation) -> String {
+    op.summary.map(str::trim).unwrap_or_default().to_string()
+}
+
+/// Left-aligns rows into space-separated columns. The last column is never
+/// padded so trailing whitespace does not leak into terminal output.
+fn columns(rows: &[Vec<String>]) -> Vec<String> {
+    let mut widths: Vec<usize> = Vec::new();
+    for row in rows {
+        for (idx, cell) in row.iter().enumerate() {
+            let len = cell.chars().count();
+            if idx >= widths.len() {
+                widths.push(len);
+            } else if len > widths[idx] {
+                widths[idx] = len;
+            }
+        }
+    }
+
+    rows.iter()
+        .map(|row| {
+            let mut line = String::new();
+            for (idx, cell) in row.iter().enumerate() {
+                if idx + 1 == row.len() {
+                    line.push_str(cell);
+                } else {
+                    line.push_str(&format!("{cell:<width$}  ", width = widths[idx]));
+                }
+            }
+            line.trim_end().to_string()
+        })
+        .collect()
+}
@@ -0,0 +1,373 @@
+use crate::command_tree::{CommandTree, Operation, Resource};
+use crate::discovery::example_invocations;
+use crate::{schema_label, write_stdout_line};
+use anyhow::{Context, Result, anyhow};
+use clap::{Arg, Command};
+use std::fs;
+use std::path::Path;
+
+pub fn docs_command() -> Command {
+    let out_arg = || {
+        Arg::new("out")
+            .long("out")
+            .value_name("DIR")
+            .required(true)
+            .help("Directory to write pages into")
+    };
+    Command::new("docs")
+        .about("Generate reference documentation from the command tree")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("man")
+                .about("Write man pages (section 1)")
+                .arg(out_arg()),
+        )
+        .subcommand(
+            Command::new("markdown")
+                .about("Write Markdown pages")
+                .arg(out_arg()),
+        )
+}
+
+pub fn handle_docs(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let (format, sub) = matches
+        .subcommand()
+        .ok_or_else(|| anyhow!("docs format required"))?;
+    let out = sub
+        .get_one::<String>("out")
+        .ok_or_else(|| anyhow!("--out required"))?;
+    let out = Path::new(out);
+    fs::create_dir_all(out).with_context(|| format!("create {}", out.display()))?;
+
+    let pages = match format {
+        "man" => man_pages(tree),
+        "markdown" => markdown_pages(tree),
+        other => return Err(anyhow!("unknown docs format: {other}")),
+    };
+
+    for (name, contents) in &pages {
+        let path = out.join(name);
+        fs::write(&path, contents).with_context(|| format!("write {}", path.display()))?;
+    }
+    write_stdout_line(&format!("wrote {} pages to {}", pages.len(), out.display()))
+}
+
+/// One documented flag, shared by both renderers.
+struct FlagDoc {
+    flag: String,
+    kind: String,
+    required: bool,
+    location: String,
+    description: String,
+}
+
+fn flag_docs(op: &Operation) -> Vec<FlagDoc> {
+    let mut out = Vec::new();
+    for param in op.params {
+        out.push(FlagDoc {
+            flag: param.flag.to_string(),
+            kind: schema_label(&param.schema),
+            required: param.required,
+            location: param.location.to_string(),
+            description: param.description.unwrap_or_default().to_string(),
+        });
+    }
+    if let Some(body) = &op.body {
+        out.push(FlagDoc {
+            flag: "body".to_string(),
+            kind: "JSON".to_string(),
+            required: false,
+            location: "body".to_string(),
+            description: "Raw JSON request body".to_string(),
+        });
+        out.push(FlagDoc {
+            flag: "body-file".to_string(),
+            kind: "PATH".to_string(),
+            required: false,
+            location: "body".to_string(),
+            description: "Path to JSON request body".to_string(),
+        });
+        for field in body.input_fields {
+            out.push(FlagDoc {
+                flag: field.flag.to_string(),
+                kind: schema_label(&field.schema),
+                required: field.required,
+                location: "body".to_string(),
+                description: field.description.unwrap_or_default().to_string(),
+            });
+        }
+    }
+    out
+}
+
+fn page_name(resource: &str, op: Option<&str>) -> String {
+    match op {
+        Some(op) => format!("n8n-{resource}-{op}"),
+        None => format!("n8n-{resource}"),
+    }
+}
+
+fn markdown_pages(tree: &CommandTree) -> Vec<(String, String)> {
+    let mut pages = Vec::new();
+
+    let mut index = String::new();
+    index.push_str("# n8n CLI reference\n\n");
+    index.push_str(&format!(
+        "Generated from n8n Public API {} (base path `{}`).\n\n",
+        tree.version, tree.base_path
+    ));
+    for res in tree.resources {
+        index.push_str(&format!(
+            "- [{}]({}.md)\n",
+            res.name,
+            page_name(res.name, None)
+        ));
+    }
+    pages.push(("index.md".to_string(), index));
+
+    for res in tree.resources {
+        pages.push((
+            format!("{}.md", page_name(res.name, None)),
+            markdown_resource(res),
+        ));
+        for op in res.visible_ops() {
+            pages.push((
+                format!("{}.md", page_name(res.name, Some(op.name))),
+                markdown_op(res.name, op),
+            ));
+        }
+    }
+    pages
+}
+
+fn markdown_resource(res: &Resource) -> String {
+    let mut out = format!("# n8n {}\n\n", res.name);
+    out.push_str("| Command | Method | Path | Summary |\n");
+    out.push_str("| --- | --- | --- | --- |\n");
+    for op in res.visible_ops() {
+        out.push_str(&format!(
+            "| [{}]({}.md) | `{}` | `{}` | {} |\n",
+            op.name,
+            page_name(res.name, Some(op.name)),
+            op.method,
+            op.path,
+            markdown_cell(op.summary.unwrap_or(""))
+        ));
+    }
+    out.push_str("\n[Back to index](index.md)\n");
+    out
+}
+
+fn markdown_op(resource: &str, op: &Operation) -> String {
+    let mut out = format!("# n8n {resource} {}\n\n", op.name);
+    if let Some(summary) = non_empty(op.summary) {
+        out.push_str(&format!("{summary}\n\n"));
+    }
+    out.push_str(&format!("`{} {}`\n\n", op.method, op.path));
+    if let Some(description) = non_empty(op.description) {
+        out.push_str("## Description\n\n");
+        out.push_str(&format!("{description}\n\n"));
+    }
+
+    let flags = flag_docs(op);
+    if !flags.is_empty() {
+        out.push_str("## Flags\n\n");
+        out.push_str("| Flag | Type | In | Required | Description |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for flag in &flags {
+            out.push_str(&format!(
+                "| `--{}` | `{}` | {} | {} | {} |\n",
+                flag.flag,
+                flag.kind,
+                flag.location,
+                if flag.required { "yes" } else { "no" },
+                markdown_cell(&flag.description)
+            ));
+        }
+        out.push('\n');
+    }
+    if let Some(body) = &op.body {
+        out.push_str(&format!(
+            "Request body: `{}`{}.\n\n",
+            body.content_type,
+            if body.required { " (required)" } else { "" }
+        ));
+    }
+
+    out.push_str("## Examples\n\n```bash\n");
+    for example in example_invocations(resource, op) {
+        out.push_str(&example);
+        out.push('\n');
+    }
+    out.push_str("```\n\n");
+    out.push_str(&format!(
+        "[Back to {resource}]({}.md)\n",
+        page_name(resource, None)
+    ));
+    out
+}
+
+fn markdown_cell(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+fn man_pages(tree: &CommandTree) -> Vec<(String, String)> {
+    let mut pages = Vec::new();
+
+    let mut root = man_header("n8n", tree.version);
+    root.push_str(".SH NAME\nn8n \\- n8n CLI (auto-generated from OpenAPI)\n");
+    root.push_str(".SH SYNOPSIS\n.B n8n\n.I resource operation\n[\\fIflags\\fR]\n");
+    root.push_str(".SH ENVIRONMENT\n");
+    root.push_str(".TP\n.B N8N_API_KEY\nAPI key sent as X-N8N-API-KEY.\n");
+    root.push_str(".TP\n.B N8N_BASE_URL\nBase URL of the n8n instance.\n");
+    root.push_str(".SH SEE ALSO\n");
+    root.push_str(&see_also(
+        tree.resources.iter().map(|r| page_name(r.name, None)),
+    ));
+    pages.push(("n8n.1".to_string(), root));
+
+    for res in tree.resources {
+        let name = page_name(res.name, None);
+        let mut page = man_header(&name, tree.version);
+        page.push_str(&format!(
+            ".SH NAME\n{} \\- {} operations\n",
+            roff_escape(&name),
+            roff_escape(res.name)
+        ));
+        page.push_str(".SH COMMANDS\n");
+        for op in res.visible_ops() {
+            page.push_str(&format!(
+                ".TP\n.B {}\n{} {}\n",
+                roff_escape(op.name),
+                op.method,
+                roff_escape(op.path)
+            ));
+            if let Some(summary) = non_empty(op.summary) {
+                page.push_str(&format!(".br\n{}\n", roff_text(summary)));
+            }
+        }
+        page.push_str(".SH SEE ALSO\n");
+        page.push_str(&see_also(
+            std::iter::once("n8n".to_string()).chain(
+                res.visible_ops()
+                    .map(|op| page_name(res.name, Some(op.name))),
+            ),
+        ));
+        pages.push((format!("{name}.1"), page));
+
+        for op in res.visible_ops() {
+            let name = page_name(res.name, Some(op.name));
+            pages.push((
+                format!("{name}.1"),
+                man_op(&name, res.name, op, tree.version),
+            ));
+        }
+    }
+    pages
+}
+
+fn man_op(name: &str, resource: &str, op: &Operation, version: &str) -> String {
+    let mut page = man_header(name, version);
+    let summary = non_empty(op.summary).unwrap_or(op.name);
+    page.push_str(&format!(
+        ".SH NAME\n{} \\- {}\n",
+        roff_escape(name),
+        roff_text(summary)
+    ));
+    page.push_str(&format!(
+        ".SH SYNOPSIS\n.B n8n {} {}\n[\\fIflags\\fR]\n",
+        roff_escape(resource),
+        roff_escape(op.name)
+    ));
+    page.push_str(&format!(
+        ".SH REQUEST\n{} {}\n",
+        op.method,
+        roff_escape(op.path)
+    ));
+    if let Some(body) = &op.body {
+        page.push_str(&format!(
+            ".br\nBody: {}{}\n",
+            roff_escape(body.content_type),
+            if body.required { " (required)" } else { "" }
+        ));
+    }
+    if let Some(description) = non_empty(op.description) {
+        page.push_str(&format!(".SH DESCRIPTION\n{}\n", roff_text(description)));
+    }
+
+    let flags = flag_docs(op);
+    if !flags.is_empty() {
+        page.push_str(".SH OPTIONS\n");
+        for flag in &flags {
+            let required = if flag.required { ", required" } else { "" };
+            page.push_str(&format!(
+                ".TP\n\\fB\\-\\-{}\\fR \\fI{}\\fR\n({}{required})",
+                roff_escape(&flag.flag),
+                roff_escape(&flag.kind),
+                flag.location
+            ));
+            if flag.description.is_empty() {
+                page.push('\n');
+            } else {
+                page.push_str(&format!(" {}\n", roff_text(&flag.description)));
+            }
+        }
+    }
+
+    page.push_str(".SH EXAMPLES\n");
+    for example in example_invocations(resource, op) {
+        page.push_str(&format!(".PP\n.nf\n{}\n.fi\n", roff_escape(&example)));
+    }
+    page.push_str(".SH SEE ALSO\n");
+    page.push_str(&see_also(
+        ["n8n".to_string(), page_name(resource, None)].into_iter(),
+    ));
+    page
+}
+
+fn man_header(name: &str, version: &str) -> String {
+    format!(
+        ".TH \"{}\" 1 \"\" \"n8n API {}\" \"n8n CLI\"\n",
+        name.to_uppercase(),
+        roff_escape(version)
+    )
+}
+
+fn see_also(names: impl Iterator<Item = String>) -> String {
+    let refs: Vec<String> = names
+        .map(|name| format!("\\fB{}\\fR(1)", roff_escape(&name)))
+        .collect();
+    format!("{}\n", refs.join(",\n"))
+}
+
+/// Escapes a single line for roff: backslashes and hyphens are literal.
+fn roff_escape(value: &str) -> String {
+    value.replace('\\', "\\e").replace('-', "\\-")
+}
+
+/// Escapes running text, guarding lines that would be read as requests.
+fn roff_text(value: &str) -> String {
+    value
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let escaped = roff_escape(line);
+            if escaped.starts_with('.') || escaped.starts_with('\'') {
+                format!("\\&{escaped}")
+            } else {
+                escaped
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|s| !s.is_empty())
+}
@@ -0,0 +1,286 @@
+//! `n8n <resource> edit`: fetch a resource, open it in `$EDITOR` and send
+//! the result back with the matching update operation.
+
+use crate::client::{self, ApiContext};
+use crate::command_tree::{CommandTree, InputField, Operation};
+use crate::export;
+use crate::{find_op, json_id, write_stdout_line};
+use anyhow::{Context, Result, anyhow};
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, Command};
+use serde_json::{Map, Value};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+/// How to read and write back one editable resource.
+pub struct Editable {
+    resource: &'static str,
+    fetch: Fetch,
+    update: &'static str,
+}
+
+enum Fetch {
+    /// A get operation taking the ID as its path parameter.
+    Get(&'static str),
+    /// No single-item get exists, so search a list operation by `id`.
+    List(&'static str),
+}
+
+const EDITABLE: &[Editable] = &[
+    Editable {
+        resource: "workflow",
+        fetch: Fetch::Get("get-workflow"),
+        update: "update-workflow",
+    },
+    Editable {
+        resource: "tags",
+        fetch: Fetch::Get("get-tag"),
+        update: "update-tag",
+    },
+    Editable {
+        resource: "variables",
+        fetch: Fetch::List("get-variables"),
+        update: "update-variable",
+    },
+    Editable {
+        resource: "projects",
+        fetch: Fetch::List("get-projects"),
+        update: "update-project",
+    },
+    Editable {
+        resource: "data-table",
+        fetch: Fetch::Get("get-data-table"),
+        update: "update-data-table",
+    },
+];
+
+const HEADER: &str = "\
+# Edit the object below and save to send it back. Lines beginning with '#'
+# at the top are ignored; an empty file aborts the edit.
+";
+
+pub fn is_editable(resource: &str) -> bool {
+    EDITABLE.iter().any(|e| e.resource == resource)
+}
+
+/// The editable resource whose update operation is `op_name`.
+pub fn by_update_op(op_name: &str) -> Option<&'static Editable> {
+    EDITABLE.iter().find(|e| e.update == op_name)
+}
+
+pub fn edit_command() -> Command {
+    Command::new("edit")
+        .about("Open the resource in $EDITOR and save it back")
+        .arg(
+            Arg::new("id")
+                .long("id")
+                .value_name("ID")
+                .required(true)
+                .help("Resource to edit"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(PossibleValuesParser::new(["yaml", "json"]))
+                .default_value("yaml")
+                .help("Format to edit in"),
+        )
+}
+
+pub fn handle_edit(tree: &CommandTree, resource: &str, matches: &clap::ArgMatches) -> Result<()> {
+    let editable = EDITABLE
+        .iter()
+        .find(|e| e.resource == resource)
+        .ok_or_else(|| anyhow!("{resource} cannot be edited"))?;
+    let ctx = ApiContext::from_env()?;
+    let id = matches
+        .get_one::<String>("id")
+        .ok_or_else(|| anyhow!("--id required"))?;
+    let format = matches
+        .get_one::<String>("format")
+        .map(String::as_str)
+        .unwrap_or("yaml");
+    let update = find_op(tree, resource, editable.update)
+        .ok_or_else(|| anyhow!("unknown command {resource} {}", editable.update))?;
+
+    let current = fetch(&ctx, tree, editable, id)?;
+    let original = writable(update, &current);
+    let path = env::temp_dir().join(format!(
+        "n8n-edit-{resource}-{id}-{}.{format}",
+        process::id()
+    ));
+    let mut text = format!("{HEADER}{}", serialize(&original, format)?);
+
+    loop {
+        fs::write(&path, &text).with_context(|| format!("write {}", path.display()))?;
+        run_editor(&path)?;
+        text = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+
+        let content = strip_comments(&text);
+        if content.trim().is_empty() {
+            let _ = fs::remove_file(&path);
+            return write_stdout_line("edit cancelled, empty file");
+        }
+        let edited = match parse(&content, format).and_then(|v| validate(update, v)) {
+            Ok(edited) => edited,
+            Err(err) => {
+                text = with_error(&content, &format!("{err:#}"));
+                continue;
+            }
+        };
+        if edited == original {
+            let _ = fs::remove_file(&path);
+            return write_stdout_line("edit cancelled, no changes made");
+        }
+
+        let args = [
+            format!("--{}", path_flag(update)?),
+            id.clone(),
+            "--body".to_string(),
+            serde_json::to_string(&edited)?,
+        ];
+        let response = client::call(&ctx, tree, resource, editable.update, &args)?;
+        if response.ok {
+            let _ = fs::remove_file(&path);
+            return write_stdout_line(&format!("edited {resource} {id}"));
+        }
+        // The server's own validation: let the user fix it in place.
+        if response.status == 400 {
+            let message = client::error_message(&response.body);
+            text = with_error(&content, &message);
+            continue;
+        }
+        return Err(anyhow!(
+            "{resource} {} failed: http error: {}: {}; your changes are in {}",
+            editable.update,
+            response.status,
+            client::error_message(&response.body),
+            path.display()
+        ));
+    }
+}
+
+pub fn fetch(ctx: &ApiContext, tree: &CommandTree, editable: &Editable, id: &str) -> Result<Value> {
+    let resource = editable.resource;
+    match editable.fetch {
+        Fetch::Get(op_name) => {
+            let op = find_op(tree, resource, op_name)
+                .ok_or_else(|| anyhow!("unknown command {resource} {op_name}"))?;
+            let args = [format!("--{}", path_flag(op)?), id.to_string()];
+            client::call_ok(ctx, tree, resource, op_name, &args)
+        }
+        Fetch::List(op_name) => client::fetch_all(ctx, tree, resource, op_name, &[])?
+            .into_iter()
+            .find(|item| json_id(item).is_some_and(|item_id| item_id == id))
+            .ok_or_else(|| anyhow!("no {resource} with id {id}")),
+    }
+}
+
+fn path_flag(op: &Operation) -> Result<&'static str> {
+    op.params
+        .iter()
+        .find(|param| param.location == "path")
+        .map(|param| param.flag)
+        .ok_or_else(|| anyhow!("{} has no path parameter", op.name))
+}
+
+/// Fields the update accepts, less the instance-owned ones export drops:
+/// the spec does not mark `shared` read-only, but sharing is changed by
+/// transfer, not by an update. Runtime fields are left out unless `runtime`
+/// is set, for callers whose copy may be older than the server's.
+pub fn editable_fields(
+    update: &Operation,
+    runtime: bool,
+) -> impl Iterator<Item = &'static InputField> + '_ {
+    update.writable_fields().filter(move |field| {
+        !export::VOLATILE_FIELDS.contains(&field.name)
+            && (runtime || !export::RUNTIME_FIELDS.contains(&field.name))
+    })
+}
+
+/// The fields of `value` the update operation accepts; read-only and
+/// unknown fields would be rejected or ignored by the server.
+pub fn writable(update: &Operation, value: &Value) -> Value {
+    let fields: Map<String, Value> = editable_fields(update, true)
+        .filter_map(|field| Some((field.name.to_string(), value.get(field.name)?.clone())))
+        .collect();
+    Value::Object(fields)
+}
+
+fn validate(update: &Operation, value: Value) -> Result<Value> {
+    let Value::Object(fields) = &value else {
+        return Err(anyhow!("expected an object"));
+    };
+    for key in fields.keys() {
+        if !editable_fields(update, true).any(|field| field.name == key) {
+            return Err(anyhow!("{key} cannot be changed"));
+        }
+    }
+    for field in editable_fields(update, true).filter(|field| field.required) {
+        if !fields.contains_key(field.name) {
+            return Err(anyhow!("{} is required", field.name));
+        }
+    }
+    Ok(value)
+}
+
+/// `$VISUAL`, then `$EDITOR`, then `vi`; the variable may carry arguments,
+/// as in `code --wait`.
+fn run_editor(path: &Path) -> Result<()> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or_else(|| anyhow!("$EDITOR is empty"))?;
+    let status = process::Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .with_context(|| format!("run editor {program}"))?;
+    if !status.success() {
+        return Err(anyhow!(
+            "editor exited with {status}; your changes are in {}",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+fn serialize(value: &Value, format: &str) -> Result<String> {
+    Ok(match format {
+        "json" => serde_json::to_string_pretty(value)? + "\n",
+        _ => serde_yaml::to_string(value)?,
+    })
+}
+
+fn parse(content: &str, format: &str) -> Result<Value> {
+    Ok(match format {
+        "json" => serde_json::from_str(content)?,
+        _ => serde_yaml::from_str(content)?,
+    })
+}
+
+/// Drops the leading `#` lines, which hold the header and any error from
+/// the previous attempt.
+fn strip_comments(text: &str) -> String {
+    let lines: Vec<&str> = text
+        .lines()
+        .skip_while(|line| line.starts_with('#'))
+        .collect();
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+fn with_error(content: &str, error: &str) -> String {
+    let mut text = HEADER.to_string();
+    text.push_str("#\n");
+    for line in error.lines() {
+        text.push_str(&format!("# error: {line}\n"));
+    }
+    text.push_str(content);
+    text
+}
@@ -0,0 +1,287 @@
+//! `n8n workflow export`: one file per workflow, normalized so that
+//! re-exporting an unchanged instance produces no diff.
+
+use crate::client::{self, ApiContext};
+use crate::command_tree::CommandTree;
+use crate::{json_id, write_stdout_line};
+use anyhow::{Context, Result, anyhow};
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgAction, Command};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Fields that change on every save or describe the instance rather than
+/// the workflow.
+pub const VOLATILE_FIELDS: &[&str] = &[
+    "updatedAt",
+    "versionId",
+    "activeVersion",
+    "activeVersionId",
+    "shared",
+];
+
+/// Fields n8n rewrites while a workflow runs, such as a polling trigger's
+/// last-seen cursor; export keeps them, but sending an old copy back makes
+/// triggers reprocess items.
+pub const RUNTIME_FIELDS: &[&str] = &["staticData"];
+
+/// Maps exported file names to the workflows they hold.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub workflows: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub id: String,
+    pub name: String,
+}
+
+impl Manifest {
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = fs::read_to_string(&path).with_context(|| format!("read {}", path.display()))?;
+        let manifest =
+            serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+        Ok(Some(manifest))
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let path = dir.join(MANIFEST_FILE);
+        fs::write(&path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("write {}", path.display()))
+    }
+}
+
+pub fn export_command() -> Command {
+    Command::new("export")
+        .about("Write every workflow to a directory, one file each")
+        .arg(
+            Arg::new("out")
+                .long("out")
+                .value_name("DIR")
+                .required(true)
+                .help("Directory to write workflow files and the manifest into"),
+        )
+        .arg(
+            Arg::new("project-id")
+                .long("project-id")
+                .value_name("ID")
+                .help("Only workflows in this project"),
+        )
+        .arg(
+            Arg::new("tags")
+                .long("tags")
+                .value_name("NAMES")
+                .help("Only workflows with all of these comma-separated tags"),
+        )
+        .arg(
+            Arg::new("active")
+                .long("active")
+                .value_name("BOOL")
+                .value_parser(PossibleValuesParser::new(["true", "false"]))
+                .help("Only active or only inactive workflows"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(PossibleValuesParser::new(["json", "yaml"]))
+                .default_value("json")
+                .help("File format"),
+        )
+        .arg(
+            Arg::new("exclude-pinned-data")
+                .long("exclude-pinned-data")
+                .action(ArgAction::SetTrue)
+                .help("Leave pinned test data out of the files"),
+        )
+}
+
+pub fn handle_export(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let ctx = ApiContext::from_env()?;
+    let out = Path::new(
+        matches
+            .get_one::<String>("out")
+            .ok_or_else(|| anyhow!("--out required"))?,
+    );
+    let format = matches
+        .get_one::<String>("format")
+        .map(String::as_str)
+        .unwrap_or("json");
+    let exclude_pinned = matches.get_flag("exclude-pinned-data");
+
+    let mut args = Vec::new();
+    let mut filtered = false;
+    for (arg, flag) in [
+        ("project-id", "--project-id"),
+        ("tags", "--tags"),
+        ("active", "--active"),
+    ] {
+        if let Some(value) = matches.get_one::<String>(arg) {
+            args.extend([flag.to_string(), value.clone()]);
+            filtered = true;
+        }
+    }
+    if exclude_pinned {
+        args.extend(["--exclude-pinned-data".to_string(), "true".to_string()]);
+    }
+    let workflows = client::fetch_all(&ctx, tree, "workflow", "get-workflows", &args)?;
+
+    fs::create_dir_all(out).with_context(|| format!("create {}", out.display()))?;
+    let previous = Manifest::load(out)?.unwrap_or_default();
+    let mut manifest = Manifest::default();
+    for workflow in workflows {
+        let workflow = normalize_workflow(workflow, exclude_pinned);
+        let id = workflow_id(&workflow)?;
+        let name = workflow
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let file = format!("{}--{id}.{format}", slug(&name));
+        let path = out.join(&file);
+        fs::write(&path, serialize(&workflow, format)?)
+            .with_context(|| format!("write {}", path.display()))?;
+        manifest.workflows.insert(file, ManifestEntry { id, name });
+    }
+
+    // Files from the last export whose workflow was renamed or deleted.
+    // A filtered export cannot tell a deleted workflow from one it filtered
+    // out, so it checks the full list and keeps files of workflows that
+    // still exist; anything the manifest never listed is left alone.
+    let stale: Vec<(&String, &ManifestEntry)> = previous
+        .workflows
+        .iter()
+        .filter(|(file, _)| !manifest.workflows.contains_key(*file))
+        .collect();
+    let remote_ids = if filtered && !stale.is_empty() {
+        let all = client::fetch_all(
+            &ctx,
+            tree,
+            "workflow",
+            "get-workflows",
+            &["--exclude-pinned-data".to_string(), "true".to_string()],
+        )?;
+        Some(
+            all.iter()
+                .map(workflow_id)
+                .collect::<Result<BTreeSet<_>>>()?,
+        )
+    } else {
+        None
+    };
+    let written: BTreeSet<String> = manifest.workflows.values().map(|e| e.id.clone()).collect();
+    let mut removed = 0;
+    for (file, entry) in stale {
+        let path = out.join(file);
+        if !path.exists() {
+            continue;
+        }
+        let gone = written.contains(&entry.id)
+            || remote_ids
+                .as_ref()
+                .is_none_or(|ids| !ids.contains(&entry.id));
+        if gone {
+            fs::remove_file(&path).with_context(|| format!("remove {}", path.display()))?;
+            removed += 1;
+        } else {
+            manifest.workflows.insert(file.clone(), entry.clone());
+        }
+    }
+    manifest.save(out)?;
+
+    let mut summary = format!("wrote {} workflows to {}", written.len(), out.display());
+    if removed > 0 {
+        summary.push_str(&format!(", removed {removed} stale files"));
+    }
+    write_stdout_line(&summary)
+}
+
+/// Drops volatile fields and reduces tags to their ID and name.
+pub fn normalize_workflow(mut workflow: Value, exclude_pinned: bool) -> Value {
+    if let Some(fields) = workflow.as_object_mut() {
+        for key in VOLATILE_FIELDS {
+            fields.remove(*key);
+        }
+        if exclude_pinned {
+            fields.remove("pinData");
+        }
+        if let Some(tags) = fields.get_mut("tags").and_then(Value::as_array_mut) {
+            for tag in tags.iter_mut() {
+                *tag = json!({ "id": tag["id"], "name": tag["name"] });
+            }
+        }
+    }
+    workflow
+}
+
+pub fn workflow_id(workflow: &Value) -> Result<String> {
+    json_id(workflow).ok_or_else(|| anyhow!("workflow without an id"))
+}
+
+/// Reads a workflow file written by export, or any JSON or YAML workflow.
+pub fn read_workflow_file(path: &Path) -> Result<Value> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    if is_yaml(path) {
+        serde_yaml::from_str(&raw).with_context(|| format!("parse {}", path.display()))
+    } else {
+        serde_json::from_str(&raw).with_context(|| format!("parse {}", path.display()))
+    }
+}
+
+/// Workflow files in `dir`, sorted by name; the manifest is not one.
+pub fn workflow_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        let is_workflow = is_yaml(&path) || path.extension().is_some_and(|e| e == "json");
+        if is_workflow && path.file_name().is_some_and(|n| n != MANIFEST_FILE) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn is_yaml(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("yaml" | "yml")
+    )
+}
+
+/// serde_json sorts object keys, which keeps the files stable.
+fn serialize(workflow: &Value, format: &str) -> Result<String> {
+    Ok(match format {
+        "yaml" => serde_yaml::to_string(workflow)?,
+        _ => serde_json::to_string_pretty(workflow)? + "\n",
+    })
+}
+
+/// Lowercase ASCII letters and digits joined by single dashes, so file
+/// names stay portable.
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for ch in name.chars() {
+        if ch.is_ascii_alphanumeric() {
+            slug.push(ch.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() {
+        "workflow".to_string()
+    } else {
+        slug.to_string()
+    }
+}
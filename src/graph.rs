@@ -0,0 +1,368 @@
+//! `n8n workflow graph`: renders a workflow's nodes and connections as a
+//! Mermaid flowchart, a Graphviz digraph or an ASCII tree.
+
+use crate::client::{self, ApiContext};
+use crate::command_tree::CommandTree;
+use crate::export;
+use crate::write_stdout_line;
+use anyhow::Result;
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgGroup, Command};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Canvas annotations, not part of the flow.
+const SKIPPED_TYPES: &[&str] = &["n8n-nodes-base.stickyNote"];
+
+/// Nodes whose outputs are branches, drawn as decisions.
+const BRANCH_TYPES: &[&str] = &[
+    "n8n-nodes-base.if",
+    "n8n-nodes-base.switch",
+    "n8n-nodes-base.filter",
+];
+
+/// Nodes that run another workflow, named by their `workflowId` parameter.
+const SUB_WORKFLOW_TYPES: &[&str] = &[
+    "n8n-nodes-base.executeWorkflow",
+    "@n8n/n8n-nodes-langchain.toolWorkflow",
+];
+
+struct Node {
+    name: String,
+    shape: Shape,
+    disabled: bool,
+    /// The workflow a sub-workflow node calls, by name when known.
+    calls: Option<String>,
+}
+
+enum Shape {
+    Trigger,
+    Branch,
+    SubWorkflow,
+    Plain,
+}
+
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+    /// Main data flow, as opposed to AI sub-node links like `ai_tool`.
+    main: bool,
+}
+
+struct Graph {
+    name: String,
+    nodes: Vec<Node>,
+    edges: Vec<Edge>,
+}
+
+pub fn graph_command() -> Command {
+    Command::new("graph")
+        .about("Draw a workflow as a Mermaid, DOT or ASCII graph")
+        .arg(
+            Arg::new("id")
+                .long("id")
+                .value_name("ID")
+                .help("Workflow to draw"),
+        )
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("PATH")
+                .help("Draw a local workflow file instead"),
+        )
+        .group(ArgGroup::new("source").args(["id", "file"]).required(true))
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(PossibleValuesParser::new(["mermaid", "dot", "ascii"]))
+                .default_value("mermaid")
+                .help("Output format"),
+        )
+}
+
+pub fn handle_graph(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let workflow = match matches.get_one::<String>("file") {
+        Some(path) => export::read_workflow_file(Path::new(path))?,
+        None => {
+            let ctx = ApiContext::from_env()?;
+            let id = matches.get_one::<String>("id").cloned().unwrap_or_default();
+            client::call_ok(
+                &ctx,
+                tree,
+                "workflow",
+                "get-workflow",
+                &["--id".to_string(), id],
+            )?
+        }
+    };
+    let graph = build_graph(&workflow);
+    let output = match matches.get_one::<String>("format").map(String::as_str) {
+        Some("dot") => dot(&graph),
+        Some("ascii") => ascii(&graph),
+        _ => mermaid(&graph),
+    };
+    write_stdout_line(output.trim_end())
+}
+
+fn build_graph(workflow: &Value) -> Graph {
+    let raw_nodes: Vec<&Value> = workflow["nodes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|node| !SKIPPED_TYPES.contains(&node["type"].as_str().unwrap_or_default()))
+        .collect();
+    let nodes: Vec<Node> = raw_nodes.iter().map(|node| build_node(node)).collect();
+    let index = |name: &str| nodes.iter().position(|node| node.name == name);
+
+    let mut edges = Vec::new();
+    for (from, raw) in raw_nodes.iter().enumerate() {
+        let Some(kinds) = workflow["connections"][nodes[from].name.as_str()].as_object() else {
+            continue;
+        };
+        for (kind, outputs) in kinds {
+            for (output, targets) in outputs.as_array().into_iter().flatten().enumerate() {
+                for target in targets.as_array().into_iter().flatten() {
+                    let Some(to) = index(target["node"].as_str().unwrap_or_default()) else {
+                        continue;
+                    };
+                    let main = kind == "main";
+                    let label = if main {
+                        output_label(raw, output)
+                    } else {
+                        Some(kind.clone())
+                    };
+                    edges.push(Edge {
+                        from,
+                        to,
+                        label,
+                        main,
+                    });
+                }
+            }
+        }
+    }
+
+    Graph {
+        name: workflow["name"].as_str().unwrap_or("workflow").to_string(),
+        nodes,
+        edges,
+    }
+}
+
+fn build_node(node: &Value) -> Node {
+    let node_type = node["type"].as_str().unwrap_or_default();
+    let calls = SUB_WORKFLOW_TYPES
+        .contains(&node_type)
+        .then(|| sub_workflow(&node["parameters"]["workflowId"]));
+    let shape = if calls.is_some() {
+        Shape::SubWorkflow
+    } else if BRANCH_TYPES.contains(&node_type) {
+        Shape::Branch
+    } else if node_type.ends_with("Trigger") || node_type == "n8n-nodes-base.webhook" {
+        Shape::Trigger
+    } else {
+        Shape::Plain
+    };
+    Node {
+        name: node["name"].as_str().unwrap_or_default().to_string(),
+        shape,
+        disabled: node["disabled"].as_bool() == Some(true),
+        calls,
+    }
+}
+
+/// `workflowId` is a plain ID in older nodes and a resource locator
+/// (`{value, cachedResultName}`) in newer ones.
+fn sub_workflow(param: &Value) -> String {
+    let name = param["cachedResultName"].as_str();
+    let id = param["value"].as_str().or(param.as_str());
+    match (name, id) {
+        (Some(name), _) => name.to_string(),
+        (None, Some(id)) => id.trim_start_matches('=').to_string(),
+        (None, None) => "another workflow".to_string(),
+    }
+}
+
+/// Names the branch an output index stands for. Nodes set to continue on
+/// error get one extra output after their regular ones.
+fn output_label(node: &Value, output: usize) -> Option<String> {
+    let node_type = node["type"].as_str().unwrap_or_default();
+    let version = node["typeVersion"].as_f64().unwrap_or(1.0);
+    let params = &node["parameters"];
+    let regular: Vec<String> = match node_type {
+        "n8n-nodes-base.if" => vec!["true".into(), "false".into()],
+        "n8n-nodes-base.filter" => vec!["kept".into(), "discarded".into()],
+        "n8n-nodes-base.splitInBatches" if version >= 3.0 => {
+            vec!["done".into(), "loop".into()]
+        }
+        "n8n-nodes-base.switch" => {
+            let rules = params["rules"]["values"]
+                .as_array()
+                .or(params["rules"]["rules"].as_array());
+            let mut labels: Vec<String> = rules
+                .into_iter()
+                .flatten()
+                .enumerate()
+                .map(|(index, rule)| match rule["outputKey"].as_str() {
+                    Some(key) if !key.is_empty() => key.to_string(),
+                    _ => index.to_string(),
+                })
+                .collect();
+            if params["options"]["fallbackOutput"].as_str() == Some("extra") {
+                labels.push("fallback".into());
+            }
+            labels
+        }
+        _ => vec![String::new()],
+    };
+    if let Some(label) = regular.get(output) {
+        return (!label.is_empty()).then(|| label.clone());
+    }
+    if node["onError"].as_str() == Some("continueErrorOutput") && output == regular.len() {
+        return Some("error".to_string());
+    }
+    Some(format!("output {output}"))
+}
+
+fn display_name(node: &Node) -> String {
+    let mut label = node.name.clone();
+    if let Some(calls) = &node.calls {
+        label.push_str(&format!(" (calls {calls})"));
+    }
+    if node.disabled {
+        label.push_str(" (disabled)");
+    }
+    label
+}
+
+fn mermaid(graph: &Graph) -> String {
+    let escape = |text: &str| text.replace('"', "#quot;");
+    let mut out = String::from("flowchart LR\n");
+    for (index, node) in graph.nodes.iter().enumerate() {
+        let label = escape(&display_name(node));
+        let shape = match node.shape {
+            Shape::Trigger => format!("([\"{label}\"])"),
+            Shape::Branch => format!("{{\"{label}\"}}"),
+            Shape::SubWorkflow => format!("[[\"{label}\"]]"),
+            Shape::Plain => format!("[\"{label}\"]"),
+        };
+        out.push_str(&format!("    n{index}{shape}\n"));
+    }
+    for edge in &graph.edges {
+        let arrow = if edge.main { "-->" } else { "-.->" };
+        let label = edge
+            .label
+            .as_ref()
+            .map(|label| format!("|\"{}\"|", escape(label)))
+            .unwrap_or_default();
+        out.push_str(&format!("    n{} {arrow}{label} n{}\n", edge.from, edge.to));
+    }
+    let disabled: Vec<String> = (0..graph.nodes.len())
+        .filter(|&index| graph.nodes[index].disabled)
+        .map(|index| format!("n{index}"))
+        .collect();
+    if !disabled.is_empty() {
+        out.push_str("    classDef disabled stroke-dasharray:5 5,color:#999\n");
+        out.push_str(&format!("    class {} disabled\n", disabled.join(",")));
+    }
+    out
+}
+
+fn dot(graph: &Graph) -> String {
+    let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+    let mut out = format!("digraph {} {{\n", quote(&graph.name));
+    out.push_str("    rankdir=LR;\n    node [shape=box];\n");
+    for (index, node) in graph.nodes.iter().enumerate() {
+        let mut attrs = vec![format!("label={}", quote(&display_name(node)))];
+        match node.shape {
+            Shape::Trigger => attrs.push("shape=oval".into()),
+            Shape::Branch => attrs.push("shape=diamond".into()),
+            Shape::SubWorkflow => attrs.push("shape=component".into()),
+            Shape::Plain => {}
+        }
+        if node.disabled {
+            attrs.push("style=dashed, fontcolor=gray".into());
+        }
+        out.push_str(&format!("    n{index} [{}];\n", attrs.join(", ")));
+    }
+    for edge in &graph.edges {
+        let mut attrs = Vec::new();
+        if let Some(label) = &edge.label {
+            attrs.push(format!("label={}", quote(label)));
+        }
+        if !edge.main {
+            attrs.push("style=dashed".into());
+        }
+        let attrs = if attrs.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", attrs.join(", "))
+        };
+        out.push_str(&format!("    n{} -> n{}{attrs};\n", edge.from, edge.to));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// A tree from each node nothing flows into. A node reached a second time
+/// (a merge or a loop) is shown once more by name but not expanded.
+fn ascii(graph: &Graph) -> String {
+    let has_incoming: HashSet<usize> = graph.edges.iter().map(|edge| edge.to).collect();
+    let mut visited = HashSet::new();
+    let mut out = String::new();
+    let roots: Vec<usize> = (0..graph.nodes.len())
+        .filter(|index| !has_incoming.contains(index))
+        .collect();
+    for root in roots {
+        ascii_root(graph, root, &mut visited, &mut out);
+    }
+    // Whatever is left sits on a cycle with no way in.
+    for index in 0..graph.nodes.len() {
+        if !visited.contains(&index) {
+            ascii_root(graph, index, &mut visited, &mut out);
+        }
+    }
+    out
+}
+
+fn ascii_root(graph: &Graph, root: usize, visited: &mut HashSet<usize>, out: &mut String) {
+    visited.insert(root);
+    out.push_str(&display_name(&graph.nodes[root]));
+    out.push('\n');
+    ascii_children(graph, root, "", visited, out);
+}
+
+fn ascii_children(
+    graph: &Graph,
+    from: usize,
+    prefix: &str,
+    visited: &mut HashSet<usize>,
+    out: &mut String,
+) {
+    let edges: Vec<&Edge> = graph
+        .edges
+        .iter()
+        .filter(|edge| edge.from == from)
+        .collect();
+    for (position, edge) in edges.iter().enumerate() {
+        let last = position + 1 == edges.len();
+        let branch = if last { "`-" } else { "+-" };
+        let arrow = match (&edge.label, edge.main) {
+            (Some(label), true) => format!("[{label}]->"),
+            (Some(label), false) => format!("[{label}]..>"),
+            (None, _) => ">".to_string(),
+        };
+        let name = display_name(&graph.nodes[edge.to]);
+        if !visited.insert(edge.to) {
+            out.push_str(&format!("{prefix}{branch}{arrow} {name} (see above)\n"));
+            continue;
+        }
+        out.push_str(&format!("{prefix}{branch}{arrow} {name}\n"));
+        let child_prefix = format!("{prefix}{}", if last { "    " } else { "|   " });
+        ascii_children(graph, edge.to, &child_prefix, visited, out);
+    }
+}
@@ -0,0 +1,534 @@
+//! `n8n workflow lint`: offline checks on workflow JSON, with severities
+//! set by a rules file and text, JSON or SARIF output for CI.
+
+use crate::client::{self, ApiContext};
+use crate::command_tree::CommandTree;
+use crate::export;
+use crate::{print_json, write_stdout_line};
+use anyhow::{Context, Result, anyhow};
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgGroup, Command};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Level {
+    Off,
+    Note,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn as_str(self) -> &'static str {
+        match self {
+            Level::Off => "off",
+            Level::Note => "note",
+            Level::Warning => "warning",
+            Level::Error => "error",
+        }
+    }
+}
+
+struct Rule {
+    id: &'static str,
+    level: Level,
+    description: &'static str,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        id: "dangling-connection",
+        level: Level::Error,
+        description: "Connection to or from a node that does not exist",
+    },
+    Rule {
+        id: "duplicate-node-name",
+        level: Level::Error,
+        description: "Two nodes share a name, so connections are ambiguous",
+    },
+    Rule {
+        id: "disconnected-node",
+        level: Level::Warning,
+        description: "Node with no incoming or outgoing connections",
+    },
+    Rule {
+        id: "missing-error-workflow",
+        level: Level::Warning,
+        description: "No error workflow is set in the workflow settings",
+    },
+    Rule {
+        id: "disabled-node",
+        level: Level::Warning,
+        description: "Disabled node left in the workflow",
+    },
+    Rule {
+        id: "hardcoded-secret",
+        level: Level::Error,
+        description: "Parameter value in a known token or API key format",
+    },
+    Rule {
+        id: "possible-secret",
+        level: Level::Warning,
+        description: "Literal value under a parameter named like a password or token",
+    },
+    Rule {
+        id: "hardcoded-url",
+        level: Level::Note,
+        description: "URL written into a parameter rather than a variable",
+    },
+    Rule {
+        id: "deprecated-type-version",
+        level: Level::Warning,
+        description: "Node type or typeVersion that n8n has deprecated",
+    },
+];
+
+/// Versions below these are deprecated in the n8n editor.
+const MIN_TYPE_VERSIONS: &[(&str, f64)] = &[
+    ("n8n-nodes-base.httpRequest", 3.0),
+    ("n8n-nodes-base.set", 3.0),
+    ("n8n-nodes-base.if", 2.0),
+    ("n8n-nodes-base.switch", 3.0),
+    ("n8n-nodes-base.merge", 2.0),
+    ("n8n-nodes-base.spreadsheetFile", 2.0),
+];
+
+/// Node types replaced outright, with their replacement.
+const REPLACED_TYPES: &[(&str, &str)] = &[
+    ("n8n-nodes-base.function", "n8n-nodes-base.code"),
+    ("n8n-nodes-base.functionItem", "n8n-nodes-base.code"),
+    ("n8n-nodes-base.cron", "n8n-nodes-base.scheduleTrigger"),
+    ("n8n-nodes-base.interval", "n8n-nodes-base.scheduleTrigger"),
+];
+
+/// Canvas annotations, never connected.
+const UNCONNECTED_TYPES: &[&str] = &["n8n-nodes-base.stickyNote"];
+
+/// Parameter names whose literal values are secrets, compared lowercase
+/// without separators.
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "apikey",
+    "authorization",
+    "accesskey",
+    "privatekey",
+];
+
+static SECRET_VALUE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\bbearer\s+[a-z0-9._~+/-]{16,}|\bsk-[a-z0-9_-]{20,}|\bgh[pousr]_[a-z0-9]{30,}|\bxox[abpr]-[a-z0-9-]{10,}|\bAKIA[0-9A-Z]{16}\b",
+    )
+    .expect("valid secret pattern")
+});
+
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"https?://[^\s'`]+").expect("valid URL pattern"));
+
+/// Severity overrides, read from `--config`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LintConfig {
+    #[serde(default)]
+    rules: BTreeMap<String, Level>,
+}
+
+#[derive(Serialize)]
+struct Finding {
+    source: String,
+    rule: &'static str,
+    level: Level,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    node: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    message: String,
+}
+
+pub fn lint_command() -> Command {
+    Command::new("lint")
+        .about("Check workflows for common mistakes, offline")
+        .arg(
+            Arg::new("file")
+                .short('f')
+                .long("file")
+                .value_name("PATH")
+                .help("Workflow file, or a directory of them"),
+        )
+        .arg(
+            Arg::new("id")
+                .long("id")
+                .value_name("ID")
+                .help("Lint a workflow fetched from the instance"),
+        )
+        .group(ArgGroup::new("source").args(["file", "id"]).required(true))
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("PATH")
+                .help("YAML or JSON file setting rule severities (error, warning, note, off)"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("FORMAT")
+                .value_parser(PossibleValuesParser::new(["text", "json", "sarif"]))
+                .default_value("text")
+                .help("Output format"),
+        )
+}
+
+pub fn handle_lint(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let config = match matches.get_one::<String>("config") {
+        Some(path) => load_config(Path::new(path))?,
+        None => LintConfig::default(),
+    };
+    let levels = rule_levels(&config)?;
+
+    let mut sources = Vec::new();
+    if let Some(path) = matches.get_one::<String>("file") {
+        let path = Path::new(path);
+        let paths = if path.is_dir() {
+            export::workflow_files(path)?
+        } else {
+            vec![path.to_path_buf()]
+        };
+        for path in paths {
+            let workflow = export::read_workflow_file(&path)?;
+            sources.push((path.display().to_string(), workflow));
+        }
+    }
+    if let Some(id) = matches.get_one::<String>("id") {
+        let ctx = ApiContext::from_env()?;
+        let args = ["--id".to_string(), id.clone()];
+        let workflow = client::call_ok(&ctx, tree, "workflow", "get-workflow", &args)?;
+        sources.push((format!("workflow/{id}"), workflow));
+    }
+
+    let mut findings = Vec::new();
+    for (source, workflow) in &sources {
+        for (rule, node, path, message) in check(workflow) {
+            let level = levels[rule];
+            if level == Level::Off {
+                continue;
+            }
+            findings.push(Finding {
+                source: source.clone(),
+                rule,
+                level,
+                node,
+                path,
+                message,
+            });
+        }
+    }
+
+    let pretty = matches.get_flag("pretty");
+    match matches.get_one::<String>("format").map(String::as_str) {
+        Some("json") => print_json(&serde_json::to_value(&findings)?, pretty)?,
+        Some("sarif") => print_json(&sarif(&findings, &levels), pretty)?,
+        _ => print_text(&findings)?,
+    }
+
+    let errors = findings.iter().filter(|f| f.level == Level::Error).count();
+    if errors > 0 {
+        return Err(anyhow!("{errors} lint errors"));
+    }
+    Ok(())
+}
+
+fn load_config(path: &Path) -> Result<LintConfig> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    serde_yaml::from_str(&raw)
+        .map_err(|err| anyhow!("invalid lint config {}: {err}", path.display()))
+}
+
+fn rule_levels(config: &LintConfig) -> Result<HashMap<&'static str, Level>> {
+    let mut levels: HashMap<&'static str, Level> =
+        RULES.iter().map(|rule| (rule.id, rule.level)).collect();
+    for (id, level) in &config.rules {
+        let rule = RULES
+            .iter()
+            .find(|rule| rule.id == id)
+            .ok_or_else(|| anyhow!("unknown lint rule {id}"))?;
+        levels.insert(rule.id, *level);
+    }
+    Ok(levels)
+}
+
+type Hit = (&'static str, Option<String>, Option<String>, String);
+
+fn check(workflow: &Value) -> Vec<Hit> {
+    let mut hits = Vec::new();
+    let nodes: Vec<&Value> = workflow["nodes"].as_array().into_iter().flatten().collect();
+    let name = |node: &Value| node["name"].as_str().unwrap_or_default().to_string();
+    let names: HashSet<String> = nodes.iter().map(|node| name(node)).collect();
+
+    let mut seen = HashSet::new();
+    for node in &nodes {
+        if !seen.insert(name(node)) {
+            hits.push((
+                "duplicate-node-name",
+                Some(name(node)),
+                None,
+                format!("another node is also named {:?}", name(node)),
+            ));
+        }
+    }
+
+    let mut connected = HashSet::new();
+    for (source, kinds) in workflow["connections"].as_object().into_iter().flatten() {
+        connected.insert(source.clone());
+        if !names.contains(source) {
+            hits.push((
+                "dangling-connection",
+                Some(source.clone()),
+                Some(format!("connections.{source}")),
+                format!("connection from {source:?}, which is not a node"),
+            ));
+        }
+        for outputs in kinds.as_object().into_iter().flat_map(|k| k.values()) {
+            for targets in outputs.as_array().into_iter().flatten() {
+                for target in targets.as_array().into_iter().flatten() {
+                    let target = target["node"].as_str().unwrap_or_default();
+                    connected.insert(target.to_string());
+                    if !names.contains(target) {
+                        hits.push((
+                            "dangling-connection",
+                            Some(source.clone()),
+                            Some(format!("connections.{source}")),
+                            format!("connection to {target:?}, which is not a node"),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    let node_type = |node: &Value| node["type"].as_str().unwrap_or_default().to_string();
+    let wired: Vec<&&Value> = nodes
+        .iter()
+        .filter(|node| !UNCONNECTED_TYPES.contains(&node_type(node).as_str()))
+        .collect();
+    if wired.len() > 1 {
+        for node in wired {
+            if !connected.contains(&name(node)) {
+                hits.push((
+                    "disconnected-node",
+                    Some(name(node)),
+                    None,
+                    "not connected to any other node".to_string(),
+                ));
+            }
+        }
+    }
+
+    let error_workflow = &workflow["settings"]["errorWorkflow"];
+    if error_workflow.as_str().is_none_or(str::is_empty) {
+        hits.push((
+            "missing-error-workflow",
+            None,
+            Some("settings.errorWorkflow".to_string()),
+            "no error workflow is set".to_string(),
+        ));
+    }
+
+    for node in &nodes {
+        if node["disabled"].as_bool() == Some(true) {
+            hits.push((
+                "disabled-node",
+                Some(name(node)),
+                None,
+                "node is disabled".to_string(),
+            ));
+        }
+        check_type_version(node, &mut hits);
+        let mut strings = Vec::new();
+        collect_strings("parameters", None, &node["parameters"], &mut strings);
+        for (path, key, value) in strings {
+            if SECRET_VALUE.is_match(&value) {
+                hits.push((
+                    "hardcoded-secret",
+                    Some(name(node)),
+                    Some(path.clone()),
+                    format!("{path} looks like a hardcoded secret (matches a token format)"),
+                ));
+            } else if let Some(key) = key.as_deref()
+                && secret_named(key, &value)
+            {
+                hits.push((
+                    "possible-secret",
+                    Some(name(node)),
+                    Some(path.clone()),
+                    format!("{path} may be a hardcoded secret (parameter named {key})"),
+                ));
+            }
+            if let Some(url) = URL.find(&value) {
+                hits.push((
+                    "hardcoded-url",
+                    Some(name(node)),
+                    Some(path.clone()),
+                    format!("{path} contains the URL {}", url.as_str()),
+                ));
+            }
+        }
+    }
+    hits
+}
+
+fn check_type_version(node: &Value, hits: &mut Vec<Hit>) {
+    let name = node["name"].as_str().unwrap_or_default().to_string();
+    let node_type = node["type"].as_str().unwrap_or_default();
+    if let Some((_, replacement)) = REPLACED_TYPES.iter().find(|(t, _)| *t == node_type) {
+        hits.push((
+            "deprecated-type-version",
+            Some(name),
+            Some("type".to_string()),
+            format!("{node_type} is deprecated; use {replacement}"),
+        ));
+        return;
+    }
+    let version = node["typeVersion"].as_f64().unwrap_or(1.0);
+    if let Some((_, min)) = MIN_TYPE_VERSIONS.iter().find(|(t, _)| *t == node_type)
+        && version < *min
+    {
+        hits.push((
+            "deprecated-type-version",
+            Some(name),
+            Some("typeVersion".to_string()),
+            format!("{node_type} version {version} is deprecated; upgrade to {min} or later"),
+        ));
+    }
+}
+
+/// Every string under `value` with its path and the parameter name it sits
+/// under. `{name, value}` pairs, as used for headers, count as parameters
+/// named `name`.
+fn collect_strings(
+    path: &str,
+    key: Option<&str>,
+    value: &Value,
+    out: &mut Vec<(String, Option<String>, String)>,
+) {
+    match value {
+        Value::String(text) => out.push((path.to_string(), key.map(str::to_string), text.clone())),
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_strings(&format!("{path}[{index}]"), key, item, out);
+            }
+        }
+        Value::Object(fields) => {
+            let pair_name = fields.get("name").and_then(Value::as_str);
+            for (field, item) in fields {
+                let key = match pair_name {
+                    Some(name) if field == "value" => name,
+                    _ => field.as_str(),
+                };
+                collect_strings(&format!("{path}.{field}"), Some(key), item, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether a literal value sits under a secret-sounding parameter name.
+/// Expressions (values starting with `=`), URLs such as a `tokenUrl`, and
+/// short single words such as `tokenType: bearer` do not count.
+fn secret_named(key: &str, value: &str) -> bool {
+    let normalized: String = key
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    let word_like = value.len() <= 20
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || c == '_' || c == '-');
+    let literal = !value.is_empty()
+        && !value.starts_with('=')
+        && !value.starts_with("http://")
+        && !value.starts_with("https://")
+        && !word_like;
+    literal && SECRET_KEYS.iter().any(|s| normalized.contains(s))
+}
+
+fn print_text(findings: &[Finding]) -> Result<()> {
+    for finding in findings {
+        let node = finding
+            .node
+            .as_ref()
+            .map(|node| format!("node {node:?}: "))
+            .unwrap_or_default();
+        write_stdout_line(&format!(
+            "{}: {}[{}] {node}{}",
+            finding.source,
+            finding.level.as_str(),
+            finding.rule,
+            finding.message
+        ))?;
+    }
+    let count = |level| findings.iter().filter(|f| f.level == level).count();
+    if findings.is_empty() {
+        return write_stdout_line("no problems found");
+    }
+    write_stdout_line(&format!(
+        "{} errors, {} warnings, {} notes",
+        count(Level::Error),
+        count(Level::Warning),
+        count(Level::Note)
+    ))
+}
+
+/// SARIF 2.1.0, the format code-scanning UIs ingest. Nodes are reported as
+/// logical locations since workflow JSON has no useful line numbers.
+fn sarif(findings: &[Finding], levels: &HashMap<&'static str, Level>) -> Value {
+    let rules: Vec<Value> = RULES
+        .iter()
+        .filter(|rule| levels[rule.id] != Level::Off)
+        .map(|rule| {
+            json!({
+                "id": rule.id,
+                "shortDescription": {"text": rule.description},
+                "defaultConfiguration": {"level": levels[rule.id].as_str()},
+            })
+        })
+        .collect();
+    let results: Vec<Value> = findings
+        .iter()
+        .map(|finding| {
+            let mut location = json!({
+                "physicalLocation": {"artifactLocation": {"uri": finding.source}},
+            });
+            if let Some(node) = &finding.node {
+                location["logicalLocations"] = json!([{"name": node, "kind": "object"}]);
+            }
+            json!({
+                "ruleId": finding.rule,
+                "level": finding.level.as_str(),
+                "message": {"text": finding.message},
+                "locations": [location],
+            })
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {"driver": {
+                "name": "n8n workflow lint",
+                "version": env!("CARGO_PKG_VERSION"),
+                "rules": rules,
+            }},
+            "results": results,
+        }],
+    })
+}
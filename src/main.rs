@@ -1,18 +1,43 @@
+mod activation;
+mod api;
+mod apply;
+mod cassette;
+mod client;
 mod command_tree;
+mod completion;
+mod credential_usage;
+mod diff;
+mod discovery;
+mod docs;
+mod edit;
+mod export;
+mod graph;
+mod lint;
+mod mcp;
+mod mock;
+mod patch;
+mod search;
+mod tools;
+mod trace;
 
 use anyhow::{Context, Result, anyhow};
+use clap::builder::PossibleValuesParser;
 use clap::{Arg, ArgAction, Command};
-use command_tree::{BodyDef, CommandTree, InputField, Operation, ParamDef, SchemaDef};
-use reqwest::Url;
+use command_tree::{BodyDef, BodyVariant, CommandTree, InputField, Operation, ParamDef, SchemaDef};
 use reqwest::blocking::Client;
+use reqwest::{StatusCode, Url};
 use serde_json::{Map, Value, json};
-use std::env;
 use std::fs;
 use std::io::Write;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
 
 fn main() {
-    if let Err(err) = run() {
+    let result = run();
+    if let Err(err) = trace::finish() {
+        eprintln!("error: {err}");
+    }
+    if let Err(err) = result {
         eprintln!("error: {err}");
         std::process::exit(1);
     }
@@ -20,24 +45,50 @@ fn main() {
 
 fn run() -> Result<()> {
     let tree = command_tree::load_command_tree();
-    let cli = build_cli(&tree);
-    let matches = cli.get_matches();
+
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some(completion::COMPLETE_IDS) {
+        return completion::handle_complete_ids(tree, &args[2..]);
+    }
+
+    let cli = build_cli_for(tree, Some(&args[1..]));
+    let matches = cli.get_matches_from(&args);
+    trace::init(
+        matches.get_count("verbose"),
+        matches.get_one::<String>("har").map(PathBuf::from),
+    );
+    cassette::init(
+        matches.get_one::<String>("record").map(PathBuf::from),
+        matches.get_one::<String>("replay").map(PathBuf::from),
+    );
 
     if let Some(matches) = matches.subcommand_matches("list") {
-        return handle_list(&tree, matches);
+        return discovery::handle_list(tree, matches);
     }
     if let Some(matches) = matches.subcommand_matches("describe") {
-        return handle_describe(&tree, matches);
+        return discovery::handle_describe(tree, matches);
     }
     if let Some(matches) = matches.subcommand_matches("tree") {
-        return handle_tree(&tree, matches);
+        return discovery::handle_tree(tree, matches);
+    }
+    if let Some(matches) = matches.subcommand_matches("completion") {
+        return completion::handle_completion(tree, matches);
+    }
+    if let Some(matches) = matches.subcommand_matches("docs") {
+        return docs::handle_docs(tree, matches);
+    }
+    if let Some(matches) = matches.subcommand_matches("tools") {
+        return tools::handle_tools(tree, matches);
+    }
+    if let Some(matches) = matches.subcommand_matches("mcp") {
+        return mcp::handle_mcp(tree, matches);
+    }
+    if let Some(matches) = matches.subcommand_matches("api") {
+        return api::handle_api(tree, matches);
+    }
+    if let Some(matches) = matches.subcommand_matches("mock") {
+        return mock::handle_mock(tree, matches);
     }
-
-    let api_key = env::var("N8N_API_KEY").context("N8N_API_KEY missing")?;
-    let base_url = env::var("N8N_BASE_URL").context("N8N_BASE_URL missing")?;
-
-    let pretty = matches.get_flag("pretty");
-    let raw = matches.get_flag("raw");
 
     let (res_name, res_matches) = matches
         .subcommand()
@@ -46,21 +97,36 @@ fn run() -> Result<()> {
         .subcommand()
         .ok_or_else(|| anyhow!("operation required"))?;
 
-    let op = find_op(&tree, res_name, op_name)
-        .ok_or_else(|| anyhow!("unknown command {res_name} {op_name}"))?;
+    if let Some(result) = handle_resource_command(tree, res_name, op_name, op_matches) {
+        return result;
+    }
 
-    let url = build_url(&base_url, &tree.base_path, op, op_matches)?;
-    let body = build_body(op, op_matches)?;
-    let response = send_request(&api_key, op, url, body)?;
+    let ctx = client::ApiContext::from_env()?;
 
-    let output = if raw { response.raw } else { response.body };
+    let pretty = matches.get_flag("pretty");
+    let raw = matches.get_flag("raw");
 
-    if pretty {
-        write_stdout_line(&serde_json::to_string_pretty(&output)?)?;
-    } else {
-        write_stdout_line(&serde_json::to_string(&output)?)?;
+    let op = find_op(tree, res_name, op_name)
+        .ok_or_else(|| anyhow!("unknown command {res_name} {op_name}"))?;
+
+    if let Some(result) = activation::handle_bulk(&ctx, tree, op, op_matches) {
+        return result;
     }
 
+    let url = build_url(&ctx.base_url, tree.base_path, op, op_matches)?;
+    let body = match patch::patched_body(&ctx, tree, op, op_matches)? {
+        Some(patched) if patched.unchanged && op_matches.get_flag("skip-unchanged") => {
+            eprintln!("no changes, update skipped");
+            return print_json(&patched.current, pretty);
+        }
+        Some(patched) => Some(patched.body),
+        None => build_body(op, op_matches)?,
+    };
+    let response = send_request(&ctx.api_key, op.method, url, body)?;
+
+    let output = if raw { response.raw } else { response.body };
+    print_json(&output, pretty)?;
+
     if !response.ok {
         return Err(anyhow!("http error: {}", response.status));
     }
@@ -69,6 +135,15 @@ fn run() -> Result<()> {
 }
 
 fn build_cli(tree: &CommandTree) -> Command {
+    build_cli_for(tree, None)
+}
+
+/// Builds the CLI, expanding only the resources and operations whose names
+/// appear in `args` (all of them when `args` is `None`). The rest are stubs
+/// carrying just their name and summary, which is all `--help` needs.
+fn build_cli_for(tree: &CommandTree, args: Option<&[String]>) -> Command {
+    let selected = |name: &str| args.is_none_or(|args| args.iter().any(|arg| arg == name));
+
     let mut cmd = Command::new("n8n")
         .about("n8n CLI (auto-generated from OpenAPI)")
         .subcommand_required(true)
@@ -86,11 +161,47 @@ fn build_cli(tree: &CommandTree) -> Command {
                 .global(true)
                 .action(ArgAction::SetTrue)
                 .help("Return full HTTP response envelope"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .global(true)
+                .action(ArgAction::Count)
+                .help("Log requests to stderr; repeat to include headers"),
+        )
+        .arg(
+            Arg::new("har")
+                .long("har")
+                .global(true)
+                .value_name("PATH")
+                .help("Write every request and response to an HTTP Archive"),
+        )
+        .arg(
+            Arg::new("record")
+                .long("record")
+                .global(true)
+                .value_name("DIR")
+                .conflicts_with("replay")
+                .help("Save every response to DIR for later --replay"),
+        )
+        .arg(
+            Arg::new("replay")
+                .long("replay")
+                .global(true)
+                .value_name("DIR")
+                .help("Answer requests from responses recorded in DIR, offline"),
         );
 
     cmd = cmd.subcommand(
         Command::new("list")
             .about("List resources and operations")
+            .arg(
+                Arg::new("search")
+                    .long("search")
+                    .value_name("TERM")
+                    .help("Fuzzy-match operation names, summaries and descriptions"),
+            )
             .arg(
                 Arg::new("json")
                     .long("json")
@@ -121,47 +232,129 @@ fn build_cli(tree: &CommandTree) -> Command {
         ),
     );
 
-    for resource in &tree.resources {
-        let mut res_cmd = Command::new(resource.name.clone())
-            .about(resource.name.clone())
+    cmd = cmd.subcommand(completion::completion_command());
+    cmd = cmd.subcommand(docs::docs_command());
+    cmd = cmd.subcommand(tools::tools_command());
+    cmd = cmd.subcommand(mcp::mcp_command());
+    cmd = cmd.subcommand(api::api_command());
+    cmd = cmd.subcommand(mock::mock_command());
+
+    for resource in tree.resources {
+        let mut res_cmd = Command::new(resource.name)
+            .about(resource.name)
+            .visible_aliases(resource.aliases)
             .subcommand_required(true)
             .arg_required_else_help(true);
-        for op in &resource.ops {
-            let mut op_cmd =
-                Command::new(op.name.clone()).about(op.summary.clone().unwrap_or_default());
-            for param in &op.params {
-                op_cmd = op_cmd.arg(build_param_arg(param));
+        if selected(resource.name) || resource.aliases.iter().any(|alias| selected(alias)) {
+            for op in resource.ops {
+                let op_cmd = if selected(op.name) || op.aliases.iter().any(|alias| selected(alias))
+                {
+                    build_op_command(op)
+                } else {
+                    Command::new(op.name).about(op.summary.unwrap_or_default())
+                };
+                res_cmd = res_cmd.subcommand(op_cmd.visible_aliases(op.aliases).hide(op.hidden));
             }
-            if let Some(body) = &op.body {
-                op_cmd = op_cmd
-                    .arg(
-                        Arg::new("body")
-                            .long("body")
-                            .value_name("JSON")
-                            .help("Raw JSON request body"),
-                    )
-                    .arg(
-                        Arg::new("body-file")
-                            .long("body-file")
-                            .value_name("PATH")
-                            .help("Path to JSON request body"),
-                    );
-
-                for field in &body.input_fields {
-                    op_cmd = op_cmd.arg(build_input_field_arg(field));
-                }
-            }
-            res_cmd = res_cmd.subcommand(op_cmd);
         }
+        res_cmd = res_cmd.subcommands(resource_commands(resource.name));
         cmd = cmd.subcommand(res_cmd);
     }
 
     cmd
 }
 
+/// Commands that sit next to a resource's generated operations but span
+/// several API calls.
+fn resource_commands(resource: &str) -> Vec<Command> {
+    let mut commands = match resource {
+        "workflow" => vec![
+            export::export_command(),
+            apply::apply_command(),
+            diff::diff_command(),
+            lint::lint_command(),
+            graph::graph_command(),
+            search::search_command(),
+            activation::restore_command(),
+        ],
+        "credential" => vec![credential_usage::usage_command()],
+        _ => Vec::new(),
+    };
+    if edit::is_editable(resource) {
+        commands.push(edit::edit_command());
+    }
+    commands
+}
+
+fn handle_resource_command(
+    tree: &CommandTree,
+    resource: &str,
+    command: &str,
+    matches: &clap::ArgMatches,
+) -> Option<Result<()>> {
+    match (resource, command) {
+        ("workflow", "export") => Some(export::handle_export(tree, matches)),
+        ("workflow", "apply") => Some(apply::handle_apply(tree, matches)),
+        ("workflow", "diff") => Some(diff::handle_diff(tree, matches)),
+        ("workflow", "lint") => Some(lint::handle_lint(tree, matches)),
+        ("workflow", "graph") => Some(graph::handle_graph(tree, matches)),
+        ("workflow", "search") => Some(search::handle_search(tree, matches)),
+        ("workflow", "restore-activation") => Some(activation::handle_restore(tree, matches)),
+        ("credential", "usage") => Some(credential_usage::handle_usage(tree, matches)),
+        (_, "edit") if edit::is_editable(resource) => {
+            Some(edit::handle_edit(tree, resource, matches))
+        }
+        _ => None,
+    }
+}
+
+fn build_op_command(op: &Operation) -> Command {
+    let mut op_cmd = Command::new(op.name).about(op.summary.unwrap_or_default());
+    for param in op.params {
+        op_cmd = op_cmd.arg(build_param_arg(param));
+    }
+    if let Some(body) = &op.body {
+        op_cmd = op_cmd
+            .arg(
+                Arg::new("body")
+                    .long("body")
+                    .value_name("JSON")
+                    .help("Raw JSON request body"),
+            )
+            .arg(
+                Arg::new("body-file")
+                    .long("body-file")
+                    .value_name("PATH")
+                    .help("Path to JSON request body"),
+            );
+
+        if !body.variants.is_empty() {
+            op_cmd = op_cmd.arg(
+                Arg::new("variant")
+                    .long("variant")
+                    .value_name("NAME")
+                    .value_parser(PossibleValuesParser::new(
+                        body.variants.iter().map(|variant| variant.name),
+                    ))
+                    .help("Body variant to build and validate against"),
+            );
+        }
+
+        for field in body.input_fields {
+            op_cmd = op_cmd.arg(build_input_field_arg(field));
+        }
+    }
+    if patch::supports(op) {
+        op_cmd = patch::add_patch_args(op_cmd);
+    }
+    if activation::supports(op) {
+        op_cmd = activation::add_selector_args(op_cmd);
+    }
+    op_cmd
+}
+
 fn build_param_arg(param: &ParamDef) -> Arg {
-    let mut arg_def = Arg::new(param.name.clone())
-        .long(param.flag.clone())
+    let mut arg_def = Arg::new(param.name)
+        .long(param.flag)
         .value_name(schema_label(&param.schema));
 
     if param.schema.kind == "array" {
@@ -178,7 +371,7 @@ fn build_param_arg(param: &ParamDef) -> Arg {
 fn build_input_field_arg(field: &InputField) -> Arg {
     let key = input_field_key(field);
     let mut arg_def = Arg::new(key)
-        .long(field.flag.clone())
+        .long(field.flag)
         .value_name(schema_label(&field.schema));
 
     if field.schema.kind == "array" {
@@ -188,86 +381,11 @@ fn build_input_field_arg(field: &InputField) -> Arg {
     arg_def
 }
 
-fn handle_list(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
-    if matches.get_flag("json") {
-        let mut out = Vec::new();
-        for res in &tree.resources {
-            let ops: Vec<String> = res.ops.iter().map(|op| op.name.clone()).collect();
-            out.push(json!({"resource": res.name, "ops": ops}));
-        }
-        write_stdout_line(&serde_json::to_string_pretty(&out)?)?;
-        return Ok(());
-    }
-
-    for res in &tree.resources {
-        write_stdout_line(&res.name)?;
-        for op in &res.ops {
-            write_stdout_line(&format!("  {}", op.name))?;
-        }
-    }
-    Ok(())
-}
-
-fn handle_describe(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
-    let resource = matches
-        .get_one::<String>("resource")
-        .ok_or_else(|| anyhow!("resource required"))?;
-    let op_name = matches
-        .get_one::<String>("op")
-        .ok_or_else(|| anyhow!("operation required"))?;
-
-    let op = find_op(tree, resource, op_name)
-        .ok_or_else(|| anyhow!("unknown command {resource} {op_name}"))?;
-
-    if matches.get_flag("json") {
-        write_stdout_line(&serde_json::to_string_pretty(op)?)?;
-        return Ok(());
-    }
-
-    write_stdout_line(&format!("{} {}", resource, op.name))?;
-    write_stdout_line(&format!("  method: {}", op.method))?;
-    write_stdout_line(&format!("  path: {}", op.path))?;
-    if let Some(summary) = &op.summary {
-        if !summary.trim().is_empty() {
-            write_stdout_line(&format!("  summary: {}", summary.trim()))?;
-        }
-    }
-    if !op.params.is_empty() {
-        write_stdout_line("  params:")?;
-        for param in &op.params {
-            write_stdout_line(&format!(
-                "    --{}  {} ({})",
-                param.flag, param.schema.kind, param.location
-            ))?;
-        }
-    }
-    if let Some(body) = &op.body {
-        write_stdout_line(&format!("  body: {}", body.content_type))?;
-        if !body.input_fields.is_empty() {
-            write_stdout_line("  body fields:")?;
-            for field in &body.input_fields {
-                write_stdout_line(&format!("    --{}  {}", field.flag, field.schema.kind))?;
-            }
-        }
-    }
-
-    Ok(())
-}
-
-fn handle_tree(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
-    if matches.get_flag("json") {
-        write_stdout_line(&serde_json::to_string_pretty(tree)?)?;
-        return Ok(());
-    }
-    write_stdout_line("Run with --json for machine-readable output.")?;
-    Ok(())
-}
-
 fn find_op<'a>(tree: &'a CommandTree, res: &str, op: &str) -> Option<&'a Operation> {
     tree.resources
         .iter()
-        .find(|r| r.name == res)
-        .and_then(|r| r.ops.iter().find(|o| o.name == op))
+        .find(|r| r.is_named(res))
+        .and_then(|r| r.ops.iter().find(|o| o.is_named(op)))
 }
 
 fn build_url(
@@ -276,29 +394,25 @@ fn build_url(
     op: &Operation,
     matches: &clap::ArgMatches,
 ) -> Result<Url> {
-    let base = base_url.trim_end_matches('/');
-    let mut base_path = base_path.trim().to_string();
-    if !base_path.starts_with('/') {
-        base_path = format!("/{base_path}");
-    }
-
-    let api_base = if base.ends_with(&base_path) {
-        base.to_string()
-    } else {
-        format!("{base}{base_path}")
-    };
-
-    let mut path = op.path.clone();
+    let mut path = op.path.to_string();
     for param in op.params.iter().filter(|p| p.location == "path") {
-        let value = matches
-            .get_one::<String>(&param.name)
-            .ok_or_else(|| anyhow!("missing required param --{}", param.flag))?;
-        let encoded = urlencoding::encode(value);
-        path = path.replace(&format!("{{{}}}", param.name), encoded.as_ref());
+        let values: Vec<String> = matches
+            .get_many::<String>(param.name)
+            .ok_or_else(|| anyhow!("missing required param --{}", param.flag))?
+            .cloned()
+            .collect();
+        let items = if param.schema.kind == "array" {
+            parse_list_for_query(&param.schema, &values)?
+        } else {
+            values
+        };
+        path = path.replace(
+            &format!("{{{}}}", param.name),
+            &path_param_value(param, &items),
+        );
     }
 
-    let url_str = format!("{api_base}{path}");
-    let mut url = Url::parse(&url_str).context("invalid N8N_BASE_URL")?;
+    let mut url = api_url(base_url, base_path, &path)?;
 
     let mut query_pairs = Vec::new();
     for param in op.params.iter().filter(|p| p.location == "query") {
@@ -314,29 +428,111 @@ fn build_url(
     Ok(url)
 }
 
+/// Joins `N8N_BASE_URL`, the spec's base path (unless the base URL already
+/// ends with it) and an operation path.
+fn api_url(base_url: &str, base_path: &str, path: &str) -> Result<Url> {
+    let base = base_url.trim_end_matches('/');
+    let mut base_path = base_path.trim().to_string();
+    if !base_path.starts_with('/') {
+        base_path = format!("/{base_path}");
+    }
+
+    let api_base = if base.ends_with(&base_path) {
+        base.to_string()
+    } else {
+        format!("{base}{base_path}")
+    };
+
+    Url::parse(&format!("{api_base}{path}")).context("invalid N8N_BASE_URL")
+}
+
+/// Serializes a path parameter following its OpenAPI `style` (`simple`,
+/// `label` or `matrix`) and `explode` setting.
+fn path_param_value(param: &ParamDef, items: &[String]) -> String {
+    let encoded: Vec<String> = items
+        .iter()
+        .map(|item| urlencoding::encode(item).into_owned())
+        .collect();
+    match param.style {
+        "label" if param.explode => format!(".{}", encoded.join(".")),
+        "label" => format!(".{}", encoded.join(",")),
+        "matrix" if param.explode => encoded
+            .iter()
+            .map(|item| format!(";{}={item}", param.name))
+            .collect(),
+        "matrix" => format!(";{}={}", param.name, encoded.join(",")),
+        _ => encoded.join(","),
+    }
+}
+
+/// Appends query pairs for a parameter following its OpenAPI `style` and
+/// `explode` setting: exploded arrays repeat the name, others are joined
+/// with the style's delimiter, and objects become `name[key]=value` pairs
+/// for `deepObject`.
 fn append_query_param(
     out: &mut Vec<(String, String)>,
     param: &ParamDef,
     matches: &clap::ArgMatches,
 ) -> Result<()> {
+    let name = param.name.to_string();
     if param.schema.kind == "array" {
-        if let Some(values) = matches.get_many::<String>(&param.name) {
+        if let Some(values) = matches.get_many::<String>(param.name) {
             let values: Vec<String> = values.cloned().collect();
             let parsed = parse_list_for_query(&param.schema, &values)?;
-            for value in parsed {
-                out.push((param.name.clone(), value));
+            if param.explode {
+                out.extend(parsed.into_iter().map(|value| (name.clone(), value)));
+            } else {
+                out.push((name, parsed.join(style_delimiter(param.style))));
             }
         }
         return Ok(());
     }
 
-    if let Some(value) = matches.get_one::<String>(&param.name) {
-        out.push((param.name.clone(), value.clone()));
+    let Some(value) = matches.get_one::<String>(param.name) else {
+        return Ok(());
+    };
+    if param.schema.kind != "object" {
+        out.push((name, value.clone()));
+        return Ok(());
+    }
+
+    let parsed: Value = serde_json::from_str(value)
+        .with_context(|| format!("invalid JSON object for --{}", param.flag))?;
+    let fields = parsed
+        .as_object()
+        .ok_or_else(|| anyhow!("--{} expects a JSON object", param.flag))?;
+    match param.style {
+        "deepObject" => {
+            for (key, field) in fields {
+                out.push((format!("{name}[{key}]"), value_to_query_string(field)?));
+            }
+        }
+        "form" if param.explode => {
+            for (key, field) in fields {
+                out.push((key.clone(), value_to_query_string(field)?));
+            }
+        }
+        style => {
+            let mut parts = Vec::new();
+            for (key, field) in fields {
+                parts.push(key.clone());
+                parts.push(value_to_query_string(field)?);
+            }
+            out.push((name, parts.join(style_delimiter(style))));
+        }
     }
 
     Ok(())
 }
 
+fn style_delimiter(style: &str) -> &'static str {
+    match style {
+        "spaceDelimited" => " ",
+        "pipeDelimited" => "|",
+        _ => ",",
+    }
+}
+
 fn parse_list_for_query(schema: &SchemaDef, values: &[String]) -> Result<Vec<String>> {
     if values.len() == 1 && values[0].trim_start().starts_with('[') {
         let parsed: Value = serde_json::from_str(&values[0]).context("invalid JSON list")?;
@@ -346,7 +542,7 @@ fn parse_list_for_query(schema: &SchemaDef, values: &[String]) -> Result<Vec<Str
         return items.iter().map(value_to_query_string).collect();
     }
 
-    let item_schema = schema.item.as_deref().unwrap_or(schema);
+    let item_schema = schema.item.unwrap_or(schema);
     values
         .iter()
         .map(|value| {
@@ -368,9 +564,8 @@ fn value_to_query_string(value: &Value) -> Result<String> {
 
 fn build_body(op: &Operation, matches: &clap::ArgMatches) -> Result<Option<Value>> {
     let Some(body) = &op.body else {
-        if matches.get_one::<String>("body").is_some()
-            || matches.get_one::<String>("body-file").is_some()
-        {
+        let given = |id: &str| matches!(matches.try_get_one::<String>(id), Ok(Some(_)));
+        if given("body") || given("body-file") {
             return Err(anyhow!("request does not accept a body"));
         }
         return Ok(None);
@@ -382,47 +577,161 @@ fn build_body(op: &Operation, matches: &clap::ArgMatches) -> Result<Option<Value
         return Err(anyhow!("use only one of --body or --body-file"));
     }
 
-    if let Some(raw) = raw_body {
-        let parsed: Value = serde_json::from_str(raw).context("invalid JSON body")?;
-        return Ok(Some(parsed));
-    }
-
-    if let Some(path) = body_file {
+    let mut value = if let Some(raw) = raw_body {
+        Some(serde_json::from_str::<Value>(raw).context("invalid JSON body")?)
+    } else if let Some(path) = body_file {
         let contents =
             fs::read_to_string(path).with_context(|| format!("failed to read body file {path}"))?;
-        let parsed: Value = serde_json::from_str(&contents).context("invalid JSON body file")?;
-        return Ok(Some(parsed));
+        Some(serde_json::from_str::<Value>(&contents).context("invalid JSON body file")?)
+    } else if (body.schema.kind == "object" || !body.variants.is_empty())
+        && !body.input_fields.is_empty()
+    {
+        build_body_from_inputs(body, matches)?
+    } else {
+        None
+    };
+
+    if let Some(value) = &mut value
+        && !body.variants.is_empty()
+    {
+        let requested = matches
+            .try_get_one::<String>("variant")
+            .ok()
+            .flatten()
+            .map(String::as_str);
+        apply_variant(body, value, requested)?;
     }
 
-    if body.schema.kind == "object" && !body.input_fields.is_empty() {
-        if let Some(obj) = build_body_from_inputs(body, matches)? {
-            return Ok(Some(obj));
+    if value.is_none() && body.required {
+        return Err(anyhow!("request body required"));
+    }
+
+    Ok(value)
+}
+
+/// Picks the variant of a polymorphic body (named by `--variant`, by the
+/// discriminator value, or the only branch whose required fields are all
+/// present), fills in its discriminator and checks its required fields.
+fn apply_variant(body: &BodyDef, value: &mut Value, requested: Option<&str>) -> Result<()> {
+    let Some(obj) = value.as_object_mut() else {
+        return Ok(());
+    };
+    let names = || {
+        body.variants
+            .iter()
+            .map(|v| v.name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let tag = body
+        .discriminator
+        .and_then(|prop| obj.get(prop))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let variant = if let Some(name) = requested {
+        body.variants
+            .iter()
+            .find(|v| v.name == name)
+            .ok_or_else(|| anyhow!("unknown variant {name}; expected one of: {}", names()))?
+    } else if let Some(tag) = &tag {
+        body.variants
+            .iter()
+            .find(|v| v.discriminator_value == Some(tag.as_str()))
+            .ok_or_else(|| {
+                anyhow!(
+                    "{} {tag:?} matches no variant; expected one of: {}",
+                    body.discriminator.unwrap_or_default(),
+                    names()
+                )
+            })?
+    } else {
+        let matching: Vec<&BodyVariant> = body
+            .variants
+            .iter()
+            .filter(|v| missing_fields(body, v, obj).is_empty())
+            .collect();
+        match matching.as_slice() {
+            [only] => *only,
+            [] => {
+                let needs: Vec<String> = body
+                    .variants
+                    .iter()
+                    .map(|v| {
+                        format!(
+                            "{} needs {}",
+                            v.name,
+                            missing_fields(body, v, obj).join(", ")
+                        )
+                    })
+                    .collect();
+                return Err(anyhow!("body matches no variant: {}", needs.join("; ")));
+            }
+            // Several branches fit; the server can tell them apart.
+            _ => return Ok(()),
+        }
+    };
+
+    if let (Some(prop), Some(expected)) = (body.discriminator, variant.discriminator_value) {
+        match &tag {
+            None => {
+                obj.insert(prop.to_string(), Value::String(expected.to_string()));
+            }
+            Some(actual) if actual != expected => {
+                return Err(anyhow!(
+                    "variant {} expects {prop} {expected:?}, body has {actual:?}",
+                    variant.name
+                ));
+            }
+            Some(_) => {}
         }
     }
 
-    if body.required {
-        return Err(anyhow!("request body required"));
+    let missing = missing_fields(body, variant, obj);
+    if !missing.is_empty() {
+        return Err(anyhow!(
+            "variant {} is missing required field(s): {}",
+            variant.name,
+            missing.join(", ")
+        ));
     }
+    Ok(())
+}
 
-    Ok(None)
+/// Required fields of `variant` absent from `obj`; a discriminator the
+/// variant can fill in itself does not count.
+fn missing_fields(
+    body: &BodyDef,
+    variant: &BodyVariant,
+    obj: &Map<String, Value>,
+) -> Vec<&'static str> {
+    let implied = body
+        .discriminator
+        .filter(|_| variant.discriminator_value.is_some());
+    variant
+        .input_fields
+        .iter()
+        .filter(|f| f.required && !obj.contains_key(f.name) && Some(f.name) != implied)
+        .map(|f| f.name)
+        .collect()
 }
 
 fn build_body_from_inputs(body: &BodyDef, matches: &clap::ArgMatches) -> Result<Option<Value>> {
     let mut obj = Map::new();
-    for field in &body.input_fields {
+    for field in body.input_fields {
         let key = input_field_key(field);
         if field.schema.kind == "array" {
             if let Some(values) = matches.get_many::<String>(&key) {
                 let values: Vec<String> = values.cloned().collect();
                 let parsed = parse_list_value(&field.schema, &values)?;
-                obj.insert(field.name.clone(), parsed);
+                obj.insert(field.name.to_string(), parsed);
             }
             continue;
         }
 
         if let Some(value) = matches.get_one::<String>(&key) {
             let parsed = parse_scalar_value(&field.schema, value)?;
-            obj.insert(field.name.clone(), parsed);
+            obj.insert(field.name.to_string(), parsed);
         }
     }
 
@@ -440,7 +749,7 @@ fn parse_list_value(schema: &SchemaDef, values: &[String]) -> Result<Value> {
     }
 
     let mut out = Vec::new();
-    let item_schema = schema.item.as_deref().unwrap_or(schema);
+    let item_schema = schema.item.unwrap_or(schema);
     for value in values {
         out.push(parse_scalar_value(item_schema, value)?);
     }
@@ -448,7 +757,17 @@ fn parse_list_value(schema: &SchemaDef, values: &[String]) -> Result<Value> {
 }
 
 fn parse_scalar_value(schema: &SchemaDef, value: &str) -> Result<Value> {
-    match schema.kind.as_str() {
+    if let Some(variants) = schema.variants {
+        // Structured branches first, so `{"a":1}` is not taken as a string.
+        let (strings, others): (Vec<&SchemaDef>, Vec<&SchemaDef>) =
+            variants.iter().partition(|v| v.kind == "string");
+        return others
+            .into_iter()
+            .chain(strings)
+            .find_map(|variant| parse_scalar_value(variant, value).ok())
+            .ok_or_else(|| anyhow!("{value:?} is not a valid {}", schema_label(schema)));
+    }
+    match schema.kind {
         "integer" => Ok(Value::Number(value.parse::<i64>()?.into())),
         "number" => Ok(json!(value.parse::<f64>()?)),
         "boolean" => Ok(Value::Bool(parse_bool(value)?)),
@@ -469,20 +788,69 @@ fn parse_bool(value: &str) -> Result<bool> {
     }
 }
 
+/// The `id` of an API object as a string; IDs are strings or numbers
+/// depending on the resource.
+fn json_id(value: &Value) -> Option<String> {
+    match value.get("id")? {
+        Value::String(id) => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Matches `value` against a pattern where `*` stands for any run of
+/// characters and `?` for a single one.
+fn glob_match(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if let Some((star_p, star_v)) = backtrack {
+            p = star_p + 1;
+            v = star_v + 1;
+            backtrack = Some((star_p, star_v + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 fn input_field_key(field: &InputField) -> String {
     format!("body__{}", field.name)
 }
 
+/// The first body field set by its own flag, for commands that build the
+/// body some other way.
+fn given_body_field(op: &Operation, matches: &clap::ArgMatches) -> Option<&'static InputField> {
+    op.body
+        .iter()
+        .flat_map(|body| body.input_fields)
+        .find(|field| matches.value_source(&input_field_key(field)).is_some())
+}
+
 fn schema_label(schema: &SchemaDef) -> String {
+    if let Some(variants) = schema.variants {
+        return variants
+            .iter()
+            .map(schema_label)
+            .collect::<Vec<_>>()
+            .join("|");
+    }
     if schema.kind == "array" {
-        let item = schema
-            .item
-            .as_ref()
-            .map(|s| s.kind.as_str())
-            .unwrap_or("unknown");
+        let item = schema.item.as_ref().map(|s| s.kind).unwrap_or("unknown");
         return format!("array<{}>", item);
     }
-    schema.kind.clone()
+    schema.kind.to_string()
 }
 
 struct HttpResponse {
@@ -494,23 +862,72 @@ struct HttpResponse {
 
 fn send_request(
     api_key: &str,
-    op: &Operation,
+    method: &str,
     url: Url,
     body: Option<Value>,
 ) -> Result<HttpResponse> {
     let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
 
-    let method = op.method.parse().context("invalid method")?;
+    let method = method.parse().context("invalid method")?;
     let mut req = client.request(method, url).header("X-N8N-API-KEY", api_key);
 
     if let Some(body) = body {
         req = req.json(&body);
     }
 
-    let res = req.send()?;
-    let status = res.status();
-    let headers = res.headers().clone();
-    let text = res.text().unwrap_or_default();
+    let request = req.build()?;
+    let request_headers = request.headers().clone();
+    let request_body = request
+        .body()
+        .and_then(|b| b.as_bytes())
+        .map(<[u8]>::to_vec);
+    let method = request.method().to_string();
+    let url = request.url().to_string();
+    let traced = trace::Request {
+        method: &method,
+        url: &url,
+        headers: &request_headers,
+        body: request_body.as_deref(),
+        started: SystemTime::now(),
+    };
+    let timer = Instant::now();
+
+    let (status, headers, text) = if let Some(replayed) =
+        cassette::replay(&method, request.url(), request_body.as_deref())?
+    {
+        let status = StatusCode::from_u16(replayed.status).context("invalid recorded status")?;
+        (status, replayed.headers, replayed.body)
+    } else {
+        let request_url = request.url().clone();
+        let res = match client.execute(request) {
+            Ok(res) => res,
+            Err(err) => {
+                let outcome = trace::Outcome::Failed(err.to_string());
+                trace::record(&traced, timer.elapsed(), &outcome);
+                return Err(err.into());
+            }
+        };
+        let status = res.status();
+        let headers = res.headers().clone();
+        let text = res.text().unwrap_or_default();
+        cassette::record(
+            &method,
+            &request_url,
+            request_body.as_deref(),
+            status.as_u16(),
+            &headers,
+            &text,
+        )?;
+        (status, headers, text)
+    };
+
+    let outcome = trace::Outcome::Response {
+        status: status.as_u16(),
+        reason: status.canonical_reason().unwrap_or(""),
+        headers: &headers,
+        body: &text,
+    };
+    trace::record(&traced, timer.elapsed(), &outcome);
 
     let body_value = if text.trim().is_empty() {
         Value::Null
@@ -538,6 +955,14 @@ fn send_request(
     })
 }
 
+fn print_json(value: &Value, pretty: bool) -> Result<()> {
+    if pretty {
+        write_stdout_line(&serde_json::to_string_pretty(value)?)
+    } else {
+        write_stdout_line(&serde_json::to_string(value)?)
+    }
+}
+
 fn write_stdout_line(value: &str) -> Result<()> {
     let mut out = std::io::stdout().lock();
     if let Err(err) = out.write_all(value.as_bytes()) {
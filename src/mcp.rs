@@ -0,0 +1,223 @@
+use crate::client::{self, ApiContext};
+use crate::command_tree::{CommandTree, Operation};
+use crate::tools::{mcp_tool, tool_name};
+use crate::{glob_match, value_to_query_string, write_stdout_line};
+use anyhow::{Result, anyhow};
+use clap::{Arg, ArgAction, Command};
+use serde_json::{Map, Value, json};
+use std::io::BufRead;
+
+const PROTOCOL_VERSIONS: [&str; 3] = ["2025-06-18", "2025-03-26", "2024-11-05"];
+
+pub fn mcp_command() -> Command {
+    Command::new("mcp")
+        .about("Model Context Protocol server")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("serve")
+                .about("Serve n8n operations as MCP tools over stdin/stdout")
+                .arg(
+                    Arg::new("allow")
+                        .long("allow")
+                        .value_name("TOOL")
+                        .action(ArgAction::Append)
+                        .help("Expose a non-GET tool, e.g. workflow_activate_workflow or 'workflow_*'"),
+                ),
+        )
+}
+
+pub fn handle_mcp(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let Some(matches) = matches.subcommand_matches("serve") else {
+        return Err(anyhow!("mcp subcommand required"));
+    };
+    let allow: Vec<String> = matches
+        .get_many::<String>("allow")
+        .map(|values| values.cloned().collect())
+        .unwrap_or_default();
+
+    let server = Server { tree, allow };
+    let stdin = std::io::stdin();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_line(&line) {
+            write_stdout_line(&serde_json::to_string(&response)?)?;
+        }
+    }
+    Ok(())
+}
+
+struct Server<'a> {
+    tree: &'a CommandTree,
+    allow: Vec<String>,
+}
+
+impl Server<'_> {
+    fn handle_line(&self, line: &str) -> Option<Value> {
+        let message: Value = match serde_json::from_str(line) {
+            Ok(message) => message,
+            Err(err) => return Some(error_response(Value::Null, -32700, &err.to_string())),
+        };
+
+        let id = message.get("id").cloned();
+        let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        // Notifications carry no id and never get a reply.
+        let id = id?;
+
+        let result = match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(self.list_tools()),
+            "tools/call" => self.call_tool(&params),
+            _ => {
+                return Some(error_response(
+                    id,
+                    -32601,
+                    &format!("unknown method {method}"),
+                ));
+            }
+        };
+
+        Some(match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err(err) => error_response(id, -32602, &err.to_string()),
+        })
+    }
+
+    fn initialize(&self, params: &Value) -> Value {
+        let requested = params.get("protocolVersion").and_then(Value::as_str);
+        let version = requested
+            .filter(|v| PROTOCOL_VERSIONS.contains(v))
+            .unwrap_or(PROTOCOL_VERSIONS[0]);
+        json!({
+            "protocolVersion": version,
+            "capabilities": {"tools": {"listChanged": false}},
+            "serverInfo": {"name": "n8n", "version": env!("CARGO_PKG_VERSION")},
+        })
+    }
+
+    fn list_tools(&self) -> Value {
+        let tools: Vec<Value> = self
+            .operations()
+            .filter(|(res, op)| self.allowed(res, op))
+            .map(|(res, op)| mcp_tool(res, op))
+            .collect();
+        json!({ "tools": tools })
+    }
+
+    fn call_tool(&self, params: &Value) -> Result<Value> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("tool name required"))?;
+        let (resource, op) = self
+            .operations()
+            .find(|(res, op)| tool_name(res, op.name) == name)
+            .ok_or_else(|| anyhow!("unknown tool {name}"))?;
+        if !self.allowed(resource, op) {
+            return Err(anyhow!(
+                "tool {name} is not allowed; restart the server with --allow {name}"
+            ));
+        }
+
+        let empty = Map::new();
+        let arguments = match params.get("arguments") {
+            Some(Value::Object(map)) => map,
+            Some(Value::Null) | None => &empty,
+            Some(_) => return Err(anyhow!("arguments must be an object")),
+        };
+        let argv = tool_arguments_to_argv(op, arguments)?;
+
+        // Request failures are tool results, not protocol errors, so the
+        // calling model can see and react to them.
+        let outcome = ApiContext::from_env()
+            .and_then(|ctx| client::call(&ctx, self.tree, resource, op.name, &argv));
+        Ok(match outcome {
+            Ok(response) => {
+                let text = serde_json::to_string_pretty(&response.body)?;
+                let text = if response.ok {
+                    text
+                } else {
+                    format!("http error: {}\n{text}", response.status)
+                };
+                json!({
+                    "content": [{"type": "text", "text": text}],
+                    "isError": !response.ok,
+                })
+            }
+            Err(err) => json!({
+                "content": [{"type": "text", "text": format!("error: {err:#}")}],
+                "isError": true,
+            }),
+        })
+    }
+
+    fn operations(&self) -> impl Iterator<Item = (&str, &Operation)> {
+        self.tree
+            .resources
+            .iter()
+            .flat_map(|res| res.visible_ops().map(move |op| (res.name, op)))
+    }
+
+    /// Read-only operations are always exposed; anything that writes has to
+    /// be named (or matched by a glob) on the allow-list.
+    fn allowed(&self, resource: &str, op: &Operation) -> bool {
+        if op.method == "GET" {
+            return true;
+        }
+        let name = tool_name(resource, op.name);
+        self.allow
+            .iter()
+            .any(|pattern| glob_match(&pattern.replace('-', "_"), &name))
+    }
+}
+
+/// Maps tool arguments onto the operation's CLI flags so the call goes
+/// through exactly the same parsing as a shell invocation. Values use the
+/// `--flag=value` form so ones starting with `-` are never read as flags.
+fn tool_arguments_to_argv(op: &Operation, arguments: &Map<String, Value>) -> Result<Vec<String>> {
+    let mut argv = Vec::new();
+    for (key, value) in arguments {
+        if value.is_null() {
+            continue;
+        }
+        if key == "body" && op.body.is_some() {
+            argv.push(format!("--body={}", serde_json::to_string(value)?));
+            continue;
+        }
+
+        let param = op
+            .params
+            .iter()
+            .find(|p| p.name == key)
+            .ok_or_else(|| anyhow!("unknown argument {key}"))?;
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    argv.push(format!("--{}={}", param.flag, value_to_query_string(item)?));
+                }
+            }
+            other => {
+                argv.push(format!(
+                    "--{}={}",
+                    param.flag,
+                    value_to_query_string(other)?
+                ));
+            }
+        }
+    }
+    Ok(argv)
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message},
+    })
+}
@@ -0,0 +1,1397 @@
+//! `n8n mock serve`: a local stand-in for the n8n public API. Requests are
+//! routed through the command tree, state lives in memory and success
+//! statuses follow the spec, so the CLI can be exercised end to end without
+//! a real instance.
+
+use crate::command_tree::{CommandTree, Operation};
+use anyhow::{Context, Result, anyhow};
+use clap::{Arg, Command};
+use reqwest::Url;
+use serde_json::{Map, Value, json};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+use std::time::SystemTime;
+use tiny_http::{Header, Response, Server};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const OWNER_EMAIL: &str = "owner@example.com";
+
+#[derive(Clone, Copy)]
+enum IdKind {
+    Short,
+    Uuid,
+    Number,
+}
+
+/// How the mock stores one top-level API collection.
+struct Collection {
+    name: &'static str,
+    id: IdKind,
+    timestamps: bool,
+    /// Server-managed fields and their initial values; request bodies
+    /// cannot set them.
+    managed: &'static str,
+    /// Stored but never returned: write-only secrets and internal sharing.
+    hidden: &'static [&'static str],
+}
+
+const COLLECTIONS: &[Collection] = &[
+    Collection {
+        name: "workflows",
+        id: IdKind::Short,
+        timestamps: true,
+        managed: r#"{"active":false,"isArchived":false,"tags":[],"shared":[],"versionId":null}"#,
+        hidden: &[],
+    },
+    Collection {
+        name: "tags",
+        id: IdKind::Short,
+        timestamps: true,
+        managed: "{}",
+        hidden: &[],
+    },
+    Collection {
+        name: "credentials",
+        id: IdKind::Short,
+        timestamps: true,
+        managed: r#"{"shared":[]}"#,
+        hidden: &["data", "shared"],
+    },
+    Collection {
+        name: "executions",
+        id: IdKind::Number,
+        timestamps: false,
+        managed: r#"{"finished":true,"mode":"manual","retryOf":null,"retrySuccessId":null,"status":"success","waitTill":null,"customData":{}}"#,
+        hidden: &[],
+    },
+    Collection {
+        name: "users",
+        id: IdKind::Uuid,
+        timestamps: true,
+        managed: r#"{"firstName":null,"lastName":null,"isPending":true,"role":"global:member"}"#,
+        hidden: &[],
+    },
+    Collection {
+        name: "projects",
+        id: IdKind::Short,
+        timestamps: false,
+        managed: r#"{"type":"team"}"#,
+        hidden: &[],
+    },
+    Collection {
+        name: "variables",
+        id: IdKind::Short,
+        timestamps: false,
+        managed: r#"{"type":"string"}"#,
+        hidden: &[],
+    },
+    Collection {
+        name: "data-tables",
+        id: IdKind::Short,
+        timestamps: true,
+        managed: r#"{"projectId":null}"#,
+        hidden: &[],
+    },
+];
+
+struct MockError {
+    status: u16,
+    message: String,
+}
+
+type Reply = std::result::Result<Value, MockError>;
+type Query = HashMap<String, String>;
+
+fn error(status: u16, message: impl Into<String>) -> MockError {
+    MockError {
+        status,
+        message: message.into(),
+    }
+}
+
+fn not_found(what: &str, id: &str) -> MockError {
+    error(404, format!("{what} {id} not found"))
+}
+
+pub fn mock_command() -> Command {
+    Command::new("mock")
+        .about("Local mock of the n8n API for offline testing")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("serve")
+                .about("Serve the API from in-memory state until interrupted")
+                .arg(
+                    Arg::new("port")
+                        .long("port")
+                        .value_name("PORT")
+                        .default_value("5678")
+                        .value_parser(clap::value_parser!(u16))
+                        .help("Port to listen on; 0 picks a free one"),
+                )
+                .arg(
+                    Arg::new("host")
+                        .long("host")
+                        .value_name("ADDR")
+                        .default_value("127.0.0.1")
+                        .help("Address to bind"),
+                )
+                .arg(
+                    Arg::new("api-key")
+                        .long("api-key")
+                        .value_name("KEY")
+                        .help("Reject requests without this X-N8N-API-KEY"),
+                )
+                .arg(
+                    Arg::new("seed").long("seed").value_name("PATH").help(
+                        "JSON or YAML file mapping collections (workflows, tags, ...) to items",
+                    ),
+                ),
+        )
+}
+
+pub fn handle_mock(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let Some(matches) = matches.subcommand_matches("serve") else {
+        return Err(anyhow!("mock subcommand required"));
+    };
+    let port = *matches.get_one::<u16>("port").unwrap_or(&5678);
+    let host = matches
+        .get_one::<String>("host")
+        .map(String::as_str)
+        .unwrap_or("127.0.0.1");
+
+    let mut mock = Mock::new(tree, matches.get_one::<String>("api-key").cloned());
+    if let Some(path) = matches.get_one::<String>("seed") {
+        let raw = fs::read_to_string(path).with_context(|| format!("read {path}"))?;
+        let seed: Value = serde_yaml::from_str(&raw).with_context(|| format!("parse {path}"))?;
+        mock.seed(&seed)?;
+    }
+
+    let server = Server::http((host, port)).map_err(|err| anyhow!("bind {host}:{port}: {err}"))?;
+    let addr = server
+        .server_addr()
+        .to_ip()
+        .ok_or_else(|| anyhow!("mock server is not listening on TCP"))?;
+    eprintln!(
+        "mock n8n API listening on http://{addr}{}",
+        tree.base_path.trim_end_matches('/')
+    );
+
+    for mut request in server.incoming_requests() {
+        let method = request.method().as_str().to_ascii_uppercase();
+        let url = request.url().to_string();
+        let api_key = request
+            .headers()
+            .iter()
+            .find(|h| h.field.equiv("X-N8N-API-KEY"))
+            .map(|h| h.value.as_str().to_string());
+        let mut body = String::new();
+        let (status, payload) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => mock.handle(&method, &url, api_key.as_deref(), &body),
+            Err(err) => (400, Some(json!({ "message": err.to_string() }))),
+        };
+        eprintln!("{method} {url} -> {status}");
+
+        let response = match payload {
+            Some(payload) => Response::from_string(payload.to_string())
+                .with_header(json_header())
+                .boxed(),
+            None => Response::empty(status).boxed(),
+        };
+        if let Err(err) = request.respond(response.with_status_code(status)) {
+            eprintln!("respond: {err}");
+        }
+    }
+    Ok(())
+}
+
+fn json_header() -> Header {
+    Header::from_bytes("Content-Type", "application/json").expect("static header is valid")
+}
+
+struct Mock<'a> {
+    tree: &'a CommandTree,
+    api_key: Option<String>,
+    items: HashMap<&'static str, Vec<Value>>,
+    /// Data table rows by table ID.
+    rows: HashMap<String, Vec<Value>>,
+    /// Project user relations by project ID.
+    members: HashMap<String, Vec<Value>>,
+    /// Every saved workflow version by version ID.
+    versions: HashMap<String, Value>,
+    counter: u64,
+}
+
+impl<'a> Mock<'a> {
+    /// Starts like a fresh instance: an owner and their personal project.
+    fn new(tree: &'a CommandTree, api_key: Option<String>) -> Self {
+        let mut mock = Mock {
+            tree,
+            api_key,
+            items: HashMap::new(),
+            rows: HashMap::new(),
+            members: HashMap::new(),
+            versions: HashMap::new(),
+            counter: 0,
+        };
+        let owner = mock.insert(
+            collection("users"),
+            json!({
+                "email": OWNER_EMAIL,
+                "firstName": "Mock",
+                "lastName": "Owner",
+                "isPending": false,
+                "role": "global:owner",
+            }),
+            true,
+        );
+        mock.insert(
+            collection("projects"),
+            json!({
+                "name": format!("Mock Owner <{OWNER_EMAIL}>"),
+                "type": "personal",
+            }),
+            true,
+        );
+        if let Some(project) = mock.personal_project() {
+            let relation = json!({ "userId": owner["id"], "role": "project:personalOwner" });
+            mock.members
+                .insert(id_string(&project["id"]), vec![relation]);
+        }
+        mock
+    }
+
+    /// Replaces whole collections with the seed's items, keeping their IDs.
+    fn seed(&mut self, seed: &Value) -> Result<()> {
+        let seed = seed
+            .as_object()
+            .ok_or_else(|| anyhow!("seed must map collection names to arrays"))?;
+        for (name, items) in seed {
+            let coll = COLLECTIONS
+                .iter()
+                .find(|c| c.name == name)
+                .ok_or_else(|| anyhow!("unknown seed collection {name}"))?;
+            let items = items
+                .as_array()
+                .ok_or_else(|| anyhow!("seed collection {name} must be an array"))?;
+            self.items.insert(coll.name, Vec::new());
+            for item in items {
+                if !item.is_object() {
+                    return Err(anyhow!("seed items in {name} must be objects"));
+                }
+                let item = self.insert(coll, item.clone(), true);
+                if coll.name == "workflows" {
+                    self.save_version(&item);
+                }
+                // Numeric IDs come from the counter; keep new ones unique.
+                if let Some(id) = item["id"].as_u64() {
+                    self.counter = self.counter.max(id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the status and, unless the spec documents none, the body.
+    fn handle(
+        &mut self,
+        method: &str,
+        url: &str,
+        api_key: Option<&str>,
+        body: &str,
+    ) -> (u16, Option<Value>) {
+        if let Some(expected) = &self.api_key
+            && api_key != Some(expected.as_str())
+        {
+            return (401, Some(json!({ "message": "unauthorized" })));
+        }
+        let Some((op, params, query)) = self.route(method, url) else {
+            return (404, Some(json!({ "message": "not found" })));
+        };
+
+        let result = parse_body(op, body).and_then(|body| self.dispatch(op, &params, &query, body));
+        match result {
+            Ok(value) => {
+                let (status, has_body) = op
+                    .response
+                    .as_ref()
+                    .map_or((200, true), |r| (r.status, r.has_body));
+                (status, has_body.then_some(value))
+            }
+            Err(err) => (err.status, Some(json!({ "message": err.message }))),
+        }
+    }
+
+    /// Matches a request against every operation's path template, preferring
+    /// templates with more literal segments (`/tags` over `/{versionId}`).
+    fn route(&self, method: &str, url: &str) -> Option<(&'a Operation, Vec<String>, Query)> {
+        let url = Url::parse(&format!("http://mock{url}")).ok()?;
+        let base_path = format!("/{}", self.tree.base_path.trim_matches('/'));
+        let path = url.path().strip_prefix(&base_path)?;
+        let segments: Vec<String> = path
+            .trim_matches('/')
+            .split('/')
+            .map(|s| urlencoding::decode(s).map_or_else(|_| s.to_string(), |s| s.into_owned()))
+            .collect();
+        let query: Query = url.query_pairs().into_owned().collect();
+
+        let mut best: Option<(usize, &'a Operation, Vec<String>)> = None;
+        for op in self.tree.resources.iter().flat_map(|r| r.ops.iter()) {
+            if op.method != method {
+                continue;
+            }
+            let template: Vec<&str> = op.path.trim_matches('/').split('/').collect();
+            if template.len() != segments.len() {
+                continue;
+            }
+            let mut params = Vec::new();
+            let mut literals = 0;
+            let matched = template.iter().zip(&segments).all(|(part, segment)| {
+                if part.starts_with('{') {
+                    params.push(segment.clone());
+                    true
+                } else {
+                    literals += 1;
+                    part == segment
+                }
+            });
+            if matched && best.as_ref().is_none_or(|(score, _, _)| literals > *score) {
+                best = Some((literals, op, params));
+            }
+        }
+        best.map(|(_, op, params)| (op, params, query))
+    }
+
+    fn dispatch(
+        &mut self,
+        op: &Operation,
+        params: &[String],
+        query: &Query,
+        body: Option<Value>,
+    ) -> Reply {
+        let id = params.first().map(String::as_str).unwrap_or_default();
+        let second = params.get(1).map(String::as_str).unwrap_or_default();
+        match (op.method, op.path) {
+            ("POST", "/workflows/{id}/activate") => self.set_active(id, true),
+            ("POST", "/workflows/{id}/deactivate") => self.set_active(id, false),
+            ("GET", "/workflows/{id}/tags") => self.workflow_tags(id),
+            ("PUT", "/workflows/{id}/tags") => self.set_workflow_tags(id, body),
+            ("GET", "/workflows/{id}/{versionId}") => self.workflow_version(id, second),
+            ("PUT", "/workflows/{id}/transfer") => self.transfer("workflows", id, body),
+            ("PUT", "/credentials/{id}/transfer") => self.transfer("credentials", id, body),
+            ("GET", "/credentials/schema/{credentialTypeName}") => {
+                Ok(json!({ "type": "object", "additionalProperties": true, "properties": {} }))
+            }
+            ("POST", "/executions/{id}/retry") => self.retry_execution(id),
+            ("POST", "/users") => self.create_users(body),
+            ("PATCH", "/users/{id}/role") => self.change_user_role(id, body),
+            ("POST", "/projects/{projectId}/users") => self.add_project_users(id, body),
+            ("PATCH", "/projects/{projectId}/users/{userId}") => {
+                self.change_project_role(id, second, body)
+            }
+            ("DELETE", "/projects/{projectId}/users/{userId}") => {
+                self.remove_project_user(id, second)
+            }
+            ("GET", "/data-tables/{dataTableId}/rows") => self.list_rows(id, query),
+            ("POST", "/data-tables/{dataTableId}/rows") => self.insert_rows(id, body),
+            ("PATCH", "/data-tables/{dataTableId}/rows/update") => {
+                self.update_rows(id, body, false)
+            }
+            ("POST", "/data-tables/{dataTableId}/rows/upsert") => self.update_rows(id, body, true),
+            ("DELETE", "/data-tables/{dataTableId}/rows/delete") => self.delete_rows(id, query),
+            ("POST", "/audit") => Ok(json!({})),
+            ("POST", "/source-control/pull") => Ok(json!({
+                "variables": { "added": [], "changed": [] },
+                "credentials": [],
+                "workflows": [],
+                "tags": { "tags": [], "mappings": [] },
+            })),
+            _ => self.generic(op, id, query, body),
+        }
+    }
+
+    /// Plain CRUD, inferred from the shape of the path: `/things` lists and
+    /// creates, `/things/{id}` reads, replaces, patches and deletes.
+    fn generic(&mut self, op: &Operation, id: &str, query: &Query, body: Option<Value>) -> Reply {
+        let template: Vec<&str> = op.path.trim_matches('/').split('/').collect();
+        let coll = COLLECTIONS.iter().find(|c| c.name == template[0]);
+        let item_path = template.len() == 2 && template[1].starts_with('{');
+        match (coll, op.method, template.len()) {
+            (Some(coll), "GET", 1) => self.list(coll, query),
+            (Some(coll), "POST", 1) => self.create(coll, body),
+            (Some(coll), "GET", 2) if item_path => {
+                let item = self.get(coll, id)?;
+                Ok(shape(coll, item.clone(), query))
+            }
+            (Some(coll), "PUT", 2) if item_path => self.update(coll, id, body, true),
+            (Some(coll), "PATCH", 2) if item_path => self.update(coll, id, body, false),
+            (Some(coll), "DELETE", 2) if item_path => self.delete(coll, id),
+            _ => Err(error(
+                501,
+                format!("{} {} is not implemented by the mock", op.method, op.path),
+            )),
+        }
+    }
+
+    fn list(&self, coll: &Collection, query: &Query) -> Reply {
+        let items: Vec<Value> = self
+            .collection_items(coll)
+            .iter()
+            .filter(|item| {
+                query
+                    .iter()
+                    .all(|(key, wanted)| self.matches_query(coll, item, key, wanted))
+            })
+            .map(|item| shape(coll, item.clone(), query))
+            .collect();
+        page(items, query)
+    }
+
+    fn create(&mut self, coll: &Collection, body: Option<Value>) -> Reply {
+        let body = body.unwrap_or_else(|| json!({}));
+        if coll.name == "tags" {
+            let name = body.get("name").and_then(Value::as_str).unwrap_or_default();
+            if self
+                .collection_items(coll)
+                .iter()
+                .any(|t| t["name"] == name)
+            {
+                return Err(error(409, format!("Tag {name} already exists")));
+            }
+        }
+        let item = self.insert(coll, body, false);
+        if coll.name == "workflows" {
+            self.save_version(&item);
+        }
+        Ok(public(coll, item))
+    }
+
+    fn update(&mut self, coll: &Collection, id: &str, body: Option<Value>, replace: bool) -> Reply {
+        let mut body = body.unwrap_or_else(|| json!({}));
+        strip_managed(coll, &mut body);
+        let version_id = (coll.name == "workflows").then(|| self.new_id(IdKind::Uuid));
+        let item = self.get_mut(coll, id)?;
+        let Some(fields) = item.as_object_mut() else {
+            return Err(error(500, "stored item is not an object"));
+        };
+        if replace {
+            let keep = managed_keys(coll);
+            fields.retain(|key, _| keep.contains(key));
+        }
+        if let Some(body) = body.as_object() {
+            fields.extend(body.clone());
+        }
+        if coll.timestamps {
+            fields.insert("updatedAt".into(), json!(now()));
+        }
+        if let Some(version_id) = version_id {
+            fields.insert("versionId".into(), version_id);
+        }
+        let item = item.clone();
+        if coll.name == "workflows" {
+            self.save_version(&item);
+        }
+        if coll.name == "tags" {
+            self.sync_tag(id, Some(&item));
+        }
+        Ok(public(coll, item))
+    }
+
+    fn delete(&mut self, coll: &Collection, id: &str) -> Reply {
+        let items = self.items.entry(coll.name).or_default();
+        let idx = items
+            .iter()
+            .position(|item| item_has_id(coll, item, id))
+            .ok_or_else(|| not_found(singular(coll), id))?;
+        let item = items.remove(idx);
+        match coll.name {
+            "tags" => self.sync_tag(id, None),
+            "data-tables" => {
+                self.rows.remove(id);
+            }
+            "projects" => {
+                self.members.remove(id);
+            }
+            "users" => {
+                for relations in self.members.values_mut() {
+                    relations.retain(|r| id_string(&r["userId"]) != id_string(&item["id"]));
+                }
+            }
+            _ => {}
+        }
+        Ok(public(coll, item))
+    }
+
+    fn set_active(&mut self, id: &str, active: bool) -> Reply {
+        let coll = collection("workflows");
+        let item = self.get_mut(coll, id)?;
+        item["active"] = json!(active);
+        item["updatedAt"] = json!(now());
+        Ok(public(coll, item.clone()))
+    }
+
+    fn workflow_tags(&self, id: &str) -> Reply {
+        let item = self.get(collection("workflows"), id)?;
+        Ok(item.get("tags").cloned().unwrap_or_else(|| json!([])))
+    }
+
+    fn set_workflow_tags(&mut self, id: &str, body: Option<Value>) -> Reply {
+        let wanted = body
+            .as_ref()
+            .and_then(Value::as_array)
+            .ok_or_else(|| error(400, "request/body must be array"))?;
+        let tags_coll = collection("tags");
+        let mut tags = Vec::new();
+        for entry in wanted {
+            let tag_id = id_string(&entry["id"]);
+            let tag = self
+                .collection_items(tags_coll)
+                .iter()
+                .find(|t| item_has_id(tags_coll, t, &tag_id))
+                .ok_or_else(|| not_found("tag", &tag_id))?;
+            tags.push(tag.clone());
+        }
+        let item = self.get_mut(collection("workflows"), id)?;
+        item["tags"] = Value::Array(tags.clone());
+        Ok(Value::Array(tags))
+    }
+
+    fn workflow_version(&self, id: &str, version_id: &str) -> Reply {
+        self.get(collection("workflows"), id)?;
+        self.versions
+            .get(version_id)
+            .filter(|v| id_string(&v["workflowId"]) == id)
+            .cloned()
+            .ok_or_else(|| not_found("workflow version", version_id))
+    }
+
+    fn transfer(&mut self, name: &str, id: &str, body: Option<Value>) -> Reply {
+        let project_id = body
+            .as_ref()
+            .and_then(|b| b.get("destinationProjectId"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                error(
+                    400,
+                    "request/body must have required property 'destinationProjectId'",
+                )
+            })?
+            .to_string();
+        let project = self.get(collection("projects"), &project_id)?.clone();
+        let coll = collection(name);
+        let item = self.get_mut(coll, id)?;
+        item["shared"] = json!([sharing(name, id, &project)]);
+        Ok(Value::Null)
+    }
+
+    fn retry_execution(&mut self, id: &str) -> Reply {
+        let coll = collection("executions");
+        let original = self.get(coll, id)?.clone();
+        let retry_id = self.new_id(IdKind::Number);
+        let mut retry = original.clone();
+        retry["id"] = retry_id.clone();
+        retry["mode"] = json!("retry");
+        retry["retryOf"] = original["id"].clone();
+        retry["retrySuccessId"] = Value::Null;
+        retry["startedAt"] = json!(now());
+        retry["stoppedAt"] = json!(now());
+        self.get_mut(coll, id)?["retrySuccessId"] = retry_id;
+        self.items.entry(coll.name).or_default().push(retry.clone());
+        Ok(retry)
+    }
+
+    fn create_users(&mut self, body: Option<Value>) -> Reply {
+        let wanted = body
+            .as_ref()
+            .and_then(Value::as_array)
+            .ok_or_else(|| error(400, "request/body must be array"))?;
+        let coll = collection("users");
+        let mut results = Vec::new();
+        for entry in wanted {
+            let email = entry
+                .get("email")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            if email.is_empty() {
+                return Err(error(
+                    400,
+                    "request/body must have required property 'email'",
+                ));
+            }
+            if self
+                .collection_items(coll)
+                .iter()
+                .any(|u| u["email"] == email)
+            {
+                results.push(json!({ "user": { "email": email }, "error": "User already exists" }));
+                continue;
+            }
+            let mut user = self.insert(coll, json!({ "email": email }), false);
+            if let Some(role) = entry.get("role") {
+                user["role"] = role.clone();
+                self.get_mut(coll, &id_string(&user["id"]))?["role"] = role.clone();
+            }
+            results.push(json!({
+                "user": {
+                    "id": user["id"],
+                    "email": email,
+                    "inviteAcceptUrl": format!("http://localhost/signup?inviteeId={}", id_string(&user["id"])),
+                    "emailSent": false,
+                },
+                "error": "",
+            }));
+        }
+        Ok(Value::Array(results))
+    }
+
+    fn change_user_role(&mut self, id: &str, body: Option<Value>) -> Reply {
+        let role = required_field(body.as_ref(), "newRoleName")?;
+        let item = self.get_mut(collection("users"), id)?;
+        item["role"] = role;
+        Ok(Value::Null)
+    }
+
+    fn add_project_users(&mut self, project_id: &str, body: Option<Value>) -> Reply {
+        self.get(collection("projects"), project_id)?;
+        let relations = required_field(body.as_ref(), "relations")?;
+        let relations = relations
+            .as_array()
+            .ok_or_else(|| error(400, "request/body/relations must be array"))?;
+        for relation in relations {
+            let user_id = id_string(&relation["userId"]);
+            self.get(collection("users"), &user_id)?;
+            let members = self.members.entry(project_id.to_string()).or_default();
+            members.retain(|m| id_string(&m["userId"]) != user_id);
+            members.push(json!({ "userId": user_id, "role": relation["role"] }));
+        }
+        Ok(Value::Null)
+    }
+
+    fn change_project_role(
+        &mut self,
+        project_id: &str,
+        user_id: &str,
+        body: Option<Value>,
+    ) -> Reply {
+        let role = required_field(body.as_ref(), "role")?;
+        self.get(collection("projects"), project_id)?;
+        let member = self
+            .members
+            .get_mut(project_id)
+            .and_then(|members| {
+                members
+                    .iter_mut()
+                    .find(|m| id_string(&m["userId"]) == user_id)
+            })
+            .ok_or_else(|| not_found("project member", user_id))?;
+        member["role"] = role;
+        Ok(Value::Null)
+    }
+
+    fn remove_project_user(&mut self, project_id: &str, user_id: &str) -> Reply {
+        self.get(collection("projects"), project_id)?;
+        let members = self.members.entry(project_id.to_string()).or_default();
+        let before = members.len();
+        members.retain(|m| id_string(&m["userId"]) != user_id);
+        if members.len() == before {
+            return Err(not_found("project member", user_id));
+        }
+        Ok(Value::Null)
+    }
+
+    fn list_rows(&self, table_id: &str, query: &Query) -> Reply {
+        self.get(collection("data-tables"), table_id)?;
+        let filter = query_filter(query)?;
+        let search = query.get("search").map(|s| s.to_lowercase());
+        let mut rows: Vec<Value> = self
+            .rows
+            .get(table_id)
+            .into_iter()
+            .flatten()
+            .filter(|row| filter.as_ref().is_none_or(|f| row_matches(row, f)))
+            .filter(|row| search.as_ref().is_none_or(|s| row_contains(row, s)))
+            .cloned()
+            .collect();
+        if let Some(sort_by) = query.get("sortBy") {
+            let (column, direction) = sort_by.split_once(':').unwrap_or((sort_by, "asc"));
+            rows.sort_by(|a, b| compare_values(&a[column], &b[column]));
+            if direction.eq_ignore_ascii_case("desc") {
+                rows.reverse();
+            }
+        }
+        page(rows, query)
+    }
+
+    fn insert_rows(&mut self, table_id: &str, body: Option<Value>) -> Reply {
+        self.get(collection("data-tables"), table_id)?;
+        let data = required_field(body.as_ref(), "data")?;
+        let data = data
+            .as_array()
+            .ok_or_else(|| error(400, "request/body/data must be array"))?;
+        let return_type = body
+            .as_ref()
+            .and_then(|b| b.get("returnType"))
+            .and_then(Value::as_str)
+            .unwrap_or("count");
+
+        let mut inserted = Vec::new();
+        for values in data {
+            inserted.push(self.insert_row(table_id, values)?);
+        }
+        Ok(match return_type {
+            "id" => inserted.iter().map(|row| row["id"].clone()).collect(),
+            "all" => Value::Array(inserted),
+            _ => json!({ "count": inserted.len() }),
+        })
+    }
+
+    fn insert_row(&mut self, table_id: &str, values: &Value) -> Reply {
+        let values = values
+            .as_object()
+            .ok_or_else(|| error(400, "rows must be objects"))?;
+        let rows = self.rows.entry(table_id.to_string()).or_default();
+        let next_id = rows
+            .iter()
+            .filter_map(|r| r["id"].as_u64())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let mut row = values.clone();
+        row.insert("id".into(), json!(next_id));
+        row.insert("createdAt".into(), json!(now()));
+        row.insert("updatedAt".into(), json!(now()));
+        rows.push(Value::Object(row.clone()));
+        Ok(Value::Object(row))
+    }
+
+    /// Update (and for upsert, insert when nothing matches) rows selected by
+    /// the body's filter.
+    fn update_rows(&mut self, table_id: &str, body: Option<Value>, upsert: bool) -> Reply {
+        self.get(collection("data-tables"), table_id)?;
+        let filter = required_field(body.as_ref(), "filter")?;
+        let data = required_field(body.as_ref(), "data")?;
+        let data = data
+            .as_object()
+            .ok_or_else(|| error(400, "request/body/data must be object"))?;
+        let flag = |name: &str| {
+            body.as_ref()
+                .and_then(|b| b.get(name))
+                .and_then(Value::as_bool)
+                .unwrap_or(false)
+        };
+        let (return_data, dry_run) = (flag("returnData"), flag("dryRun"));
+
+        let mut changed = Vec::new();
+        for row in self.rows.entry(table_id.to_string()).or_default() {
+            if !row_matches(row, &filter) {
+                continue;
+            }
+            let mut updated = row.clone();
+            if let Some(fields) = updated.as_object_mut() {
+                fields.extend(data.clone());
+                fields.insert("updatedAt".into(), json!(now()));
+            }
+            if !dry_run {
+                *row = updated.clone();
+            }
+            changed.push(updated);
+        }
+        if upsert && changed.is_empty() && !dry_run {
+            changed.push(self.insert_row(table_id, &Value::Object(data.clone()))?);
+        }
+        Ok(if return_data {
+            Value::Array(changed)
+        } else {
+            json!(true)
+        })
+    }
+
+    fn delete_rows(&mut self, table_id: &str, query: &Query) -> Reply {
+        self.get(collection("data-tables"), table_id)?;
+        let filter = query_filter(query)?
+            .ok_or_else(|| error(400, "request/query must have required property 'filter'"))?;
+        let flag = |name: &str| query.get(name).is_some_and(|v| v == "true");
+        let rows = self.rows.entry(table_id.to_string()).or_default();
+        let (deleted, kept): (Vec<Value>, Vec<Value>) =
+            rows.drain(..).partition(|row| row_matches(row, &filter));
+        *rows = if flag("dryRun") {
+            let mut all = kept;
+            all.extend(deleted.iter().cloned());
+            all.sort_by(|a, b| compare_values(&a["id"], &b["id"]));
+            all
+        } else {
+            kept
+        };
+        Ok(if flag("returnData") {
+            Value::Array(deleted)
+        } else {
+            json!(true)
+        })
+    }
+
+    fn collection_items(&self, coll: &Collection) -> &[Value] {
+        self.items.get(coll.name).map_or(&[], Vec::as_slice)
+    }
+
+    fn get(&self, coll: &Collection, id: &str) -> std::result::Result<&Value, MockError> {
+        self.collection_items(coll)
+            .iter()
+            .find(|item| item_has_id(coll, item, id))
+            .ok_or_else(|| not_found(singular(coll), id))
+    }
+
+    fn get_mut(
+        &mut self,
+        coll: &Collection,
+        id: &str,
+    ) -> std::result::Result<&mut Value, MockError> {
+        self.items
+            .entry(coll.name)
+            .or_default()
+            .iter_mut()
+            .find(|item| item_has_id(coll, item, id))
+            .ok_or_else(|| not_found(singular(coll), id))
+    }
+
+    /// Stores a new item with server-managed fields filled in. Trusted items
+    /// (seeds and the mock's own) keep what they set; request bodies cannot
+    /// set managed fields.
+    fn insert(&mut self, coll: &Collection, mut body: Value, trusted: bool) -> Value {
+        if !trusted {
+            strip_managed(coll, &mut body);
+        }
+        let mut item = Map::new();
+        let id = match body.get("id") {
+            Some(id) if trusted => id.clone(),
+            _ => self.new_id(coll.id),
+        };
+        item.insert("id".into(), id.clone());
+        if let Ok(Value::Object(defaults)) = serde_json::from_str::<Value>(coll.managed) {
+            item.extend(defaults);
+        }
+        if coll.timestamps {
+            item.insert("createdAt".into(), json!(now()));
+            item.insert("updatedAt".into(), json!(now()));
+        }
+        match coll.name {
+            "workflows" => {
+                item.insert("versionId".into(), self.new_id(IdKind::Uuid));
+                if let Some(project) = self.personal_project() {
+                    let sharing = sharing(coll.name, &id_string(&id), &project);
+                    item.insert("shared".into(), json!([sharing]));
+                }
+            }
+            "data-tables" => {
+                let project_id = self.personal_project().map(|p| p["id"].clone());
+                item.insert("projectId".into(), project_id.unwrap_or(Value::Null));
+            }
+            _ => {}
+        }
+        if let Some(body) = body.as_object() {
+            item.extend(body.clone());
+        }
+        let item = Value::Object(item);
+        self.items.entry(coll.name).or_default().push(item.clone());
+        item
+    }
+
+    fn save_version(&mut self, workflow: &Value) {
+        let version_id = id_string(&workflow["versionId"]);
+        self.versions.insert(
+            version_id.clone(),
+            json!({
+                "versionId": version_id,
+                "workflowId": workflow["id"],
+                "nodes": workflow.get("nodes").cloned().unwrap_or_else(|| json!([])),
+                "connections": workflow.get("connections").cloned().unwrap_or_else(|| json!({})),
+                "authors": "Mock Owner",
+                "name": workflow["name"],
+                "createdAt": now(),
+                "updatedAt": now(),
+            }),
+        );
+    }
+
+    /// Keeps the tag copies embedded in workflows in step with the tag.
+    fn sync_tag(&mut self, id: &str, tag: Option<&Value>) {
+        for workflow in self.items.entry("workflows").or_default() {
+            let Some(tags) = workflow.get_mut("tags").and_then(Value::as_array_mut) else {
+                continue;
+            };
+            match tag {
+                Some(tag) => tags
+                    .iter_mut()
+                    .filter(|t| id_string(&t["id"]) == id)
+                    .for_each(|t| *t = tag.clone()),
+                None => tags.retain(|t| id_string(&t["id"]) != id),
+            }
+        }
+    }
+
+    fn personal_project(&self) -> Option<Value> {
+        self.collection_items(collection("projects"))
+            .iter()
+            .find(|p| p["type"] == "personal")
+            .cloned()
+    }
+
+    fn matches_query(&self, coll: &Collection, item: &Value, key: &str, wanted: &str) -> bool {
+        match key {
+            "limit" | "cursor" => true,
+            _ if key.starts_with("include") || key.starts_with("exclude") => true,
+            "tags" => {
+                let names: Vec<&str> = item["tags"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|t| t["name"].as_str())
+                    .collect();
+                wanted
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .all(|t| names.contains(&t))
+            }
+            "projectId" if coll.name == "users" => self
+                .members
+                .get(wanted)
+                .is_some_and(|m| m.iter().any(|r| r["userId"] == item["id"])),
+            "projectId" => project_ids(item).iter().any(|id| id == wanted),
+            "filter" => serde_json::from_str(wanted).is_ok_and(|f| row_matches(item, &f)),
+            _ => item.get(key).is_none_or(|value| id_string(value) == wanted),
+        }
+    }
+
+    fn new_id(&mut self, kind: IdKind) -> Value {
+        self.counter += 1;
+        if let IdKind::Number = kind {
+            return json!(self.counter);
+        }
+        // Deterministic, so the same session against a fresh mock always
+        // produces the same IDs.
+        let digest = Sha256::digest(self.counter.to_string().as_bytes());
+        match kind {
+            IdKind::Uuid => {
+                let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+                json!(format!(
+                    "{}-{}-{}-{}-{}",
+                    &hex[..8],
+                    &hex[8..12],
+                    &hex[12..16],
+                    &hex[16..20],
+                    &hex[20..32]
+                ))
+            }
+            _ => {
+                const ALPHABET: &[u8] =
+                    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+                let id: String = digest[..16]
+                    .iter()
+                    .map(|b| ALPHABET[usize::from(*b) % ALPHABET.len()] as char)
+                    .collect();
+                json!(id)
+            }
+        }
+    }
+}
+
+fn collection(name: &str) -> &'static Collection {
+    COLLECTIONS
+        .iter()
+        .find(|c| c.name == name)
+        .expect("known mock collection")
+}
+
+fn singular(coll: &Collection) -> &str {
+    coll.name.trim_end_matches('s')
+}
+
+fn managed_keys(coll: &Collection) -> Vec<String> {
+    let mut keys: Vec<String> = ["id", "createdAt", "updatedAt"]
+        .map(str::to_string)
+        .to_vec();
+    if let Ok(Value::Object(defaults)) = serde_json::from_str::<Value>(coll.managed) {
+        keys.extend(defaults.keys().cloned());
+    }
+    keys
+}
+
+fn strip_managed(coll: &Collection, body: &mut Value) {
+    let managed = managed_keys(coll);
+    if let Some(fields) = body.as_object_mut() {
+        fields.retain(|key, _| !managed.contains(key));
+    }
+}
+
+fn item_has_id(coll: &Collection, item: &Value, id: &str) -> bool {
+    id_string(&item["id"]) == id || (coll.name == "users" && item["email"] == id)
+}
+
+/// IDs and query values compare as strings: `1`, `"1"` and `true` alike.
+fn id_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn now() -> String {
+    humantime::format_rfc3339_millis(SystemTime::now()).to_string()
+}
+
+fn sharing(name: &str, id: &str, project: &Value) -> Value {
+    let role = if name == "workflows" {
+        "workflow:owner"
+    } else {
+        "credential:owner"
+    };
+    let id_key = if name == "workflows" {
+        "workflowId"
+    } else {
+        "credentialsId"
+    };
+    json!({
+        "role": role,
+        id_key: id,
+        "projectId": project["id"],
+        "project": { "id": project["id"], "name": project["name"], "type": project["type"] },
+    })
+}
+
+fn project_ids(item: &Value) -> Vec<String> {
+    let mut ids: Vec<String> = item["shared"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|s| id_string(&s["projectId"]))
+        .collect();
+    if let Some(id) = item.get("projectId") {
+        ids.push(id_string(id));
+    }
+    if let Some(project) = item.get("project") {
+        ids.push(id_string(&project["id"]));
+    }
+    ids
+}
+
+/// Drops hidden fields and the parts a query opted out of.
+fn shape(coll: &Collection, item: Value, query: &Query) -> Value {
+    let mut item = public(coll, item);
+    let flag = |name: &str| query.get(name).is_some_and(|v| v == "true");
+    if let Some(fields) = item.as_object_mut() {
+        match coll.name {
+            "executions" if !flag("includeData") => {
+                fields.remove("data");
+            }
+            "workflows" if flag("excludePinnedData") => {
+                fields.remove("pinData");
+            }
+            "users" if !flag("includeRole") => {
+                fields.remove("role");
+            }
+            _ => {}
+        }
+    }
+    item
+}
+
+fn public(coll: &Collection, mut item: Value) -> Value {
+    if let Some(fields) = item.as_object_mut() {
+        for key in coll.hidden {
+            fields.remove(*key);
+        }
+    }
+    item
+}
+
+fn page(items: Vec<Value>, query: &Query) -> Reply {
+    let limit = match query.get("limit") {
+        Some(limit) => limit
+            .parse::<usize>()
+            .ok()
+            .filter(|l| *l > 0)
+            .ok_or_else(|| error(400, "request/query/limit must be a positive integer"))?,
+        None => DEFAULT_PAGE_SIZE,
+    };
+    let start = match query.get("cursor") {
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .map_err(|_| error(400, "invalid cursor"))?,
+        None => 0,
+    };
+    let end = (start + limit).min(items.len());
+    let next = (end < items.len()).then(|| end.to_string());
+    let data: Vec<Value> = items.into_iter().skip(start).take(limit).collect();
+    Ok(json!({ "data": data, "nextCursor": next }))
+}
+
+fn parse_body(op: &Operation, raw: &str) -> std::result::Result<Option<Value>, MockError> {
+    if raw.trim().is_empty() {
+        if op.body.as_ref().is_some_and(|b| b.required) {
+            return Err(error(400, "request/body is required"));
+        }
+        return Ok(None);
+    }
+    let value: Value = serde_json::from_str(raw)
+        .map_err(|err| error(400, format!("request body is not valid JSON: {err}")))?;
+    if let Some(def) = &op.body
+        && def.variants.is_empty()
+        && let Some(fields) = value.as_object()
+        && let Some(missing) = def
+            .input_fields
+            .iter()
+            .find(|f| f.required && !fields.contains_key(f.name))
+    {
+        return Err(error(
+            400,
+            format!(
+                "request/body must have required property '{}'",
+                missing.name
+            ),
+        ));
+    }
+    Ok(Some(value))
+}
+
+fn required_field(body: Option<&Value>, name: &str) -> std::result::Result<Value, MockError> {
+    body.and_then(|b| b.get(name)).cloned().ok_or_else(|| {
+        error(
+            400,
+            format!("request/body must have required property '{name}'"),
+        )
+    })
+}
+
+fn query_filter(query: &Query) -> std::result::Result<Option<Value>, MockError> {
+    query
+        .get("filter")
+        .map(|raw| {
+            serde_json::from_str(raw)
+                .map_err(|err| error(400, format!("filter is not valid JSON: {err}")))
+        })
+        .transpose()
+}
+
+/// Data table filters: `{"type": "and"|"or", "filters": [{columnName,
+/// condition, value}]}` with eq, neq, like, ilike, gt, gte, lt and lte.
+fn row_matches(row: &Value, filter: &Value) -> bool {
+    let conditions = filter["filters"].as_array().map_or(&[][..], Vec::as_slice);
+    let check = |c: &Value| {
+        let column = c["columnName"].as_str().unwrap_or_default();
+        let actual = row.get(column).unwrap_or(&Value::Null);
+        let expected = &c["value"];
+        let ordering = compare_values(actual, expected);
+        match c["condition"].as_str().unwrap_or("eq") {
+            "neq" => actual != expected,
+            "like" => like(actual, expected, false),
+            "ilike" => like(actual, expected, true),
+            "gt" => ordering == Ordering::Greater,
+            "gte" => ordering != Ordering::Less,
+            "lt" => ordering == Ordering::Less,
+            "lte" => ordering != Ordering::Greater,
+            _ => actual == expected,
+        }
+    };
+    if filter["type"] == "or" {
+        conditions.is_empty() || conditions.iter().any(check)
+    } else {
+        conditions.iter().all(check)
+    }
+}
+
+/// SQL `LIKE`: `%` matches any run of characters, `_` a single one and a
+/// backslash makes the next character literal.
+fn like(actual: &Value, pattern: &Value, ignore_case: bool) -> bool {
+    let (Some(actual), Some(pattern)) = (actual.as_str(), pattern.as_str()) else {
+        return false;
+    };
+    let fold = |s: &str| -> Vec<char> {
+        if ignore_case {
+            s.to_lowercase().chars().collect()
+        } else {
+            s.chars().collect()
+        }
+    };
+    let text = fold(actual);
+    let mut tokens = Vec::new();
+    let mut chars = fold(pattern).into_iter();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '%' => LikeToken::Any,
+            '_' => LikeToken::One,
+            '\\' => LikeToken::Literal(chars.next().unwrap_or('\\')),
+            c => LikeToken::Literal(c),
+        });
+    }
+
+    let (mut p, mut t) = (0, 0);
+    // Where the last `%` was and how much text it has swallowed so far.
+    let mut any: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match tokens.get(p) {
+            Some(LikeToken::One) => {
+                p += 1;
+                t += 1;
+            }
+            Some(LikeToken::Literal(c)) if *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            Some(LikeToken::Any) => {
+                any = Some((p, t));
+                p += 1;
+            }
+            _ => match any {
+                Some((any_p, any_t)) => {
+                    p = any_p + 1;
+                    t = any_t + 1;
+                    any = Some((any_p, any_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    tokens[p..]
+        .iter()
+        .all(|token| matches!(token, LikeToken::Any))
+}
+
+enum LikeToken {
+    Any,
+    One,
+    Literal(char),
+}
+
+fn row_contains(row: &Value, needle: &str) -> bool {
+    row.as_object().is_some_and(|fields| {
+        fields
+            .values()
+            .filter_map(Value::as_str)
+            .any(|s| s.to_lowercase().contains(needle))
+    })
+}
+
+fn compare_values(a: &Value, b: &Value) -> Ordering {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => id_string(a).cmp(&id_string(b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command_tree::load_command_tree;
+
+    fn mock() -> Mock<'static> {
+        let mut mock = Mock::new(load_command_tree(), None);
+        mock.seed(&json!({
+            "workflows": [
+                { "id": "w1", "name": "Alpha", "active": true, "tags": [{ "id": "t1", "name": "prod" }] },
+                { "id": "w2", "name": "Beta", "active": false, "tags": [{ "id": "t1", "name": "prod" }, { "id": "t2", "name": "billing" }] },
+                { "id": "w3", "name": "Gamma", "active": false },
+            ],
+            "data-tables": [{ "id": "dt1", "name": "Orders", "columns": [] }],
+        }))
+        .unwrap();
+        let rows = json!({ "data": [
+            { "sku": "A_1", "qty": 1 },
+            { "sku": "AB1", "qty": 5 },
+            { "sku": "50% off", "qty": 9 },
+        ] });
+        let (status, _) = mock.handle(
+            "POST",
+            "/api/v1/data-tables/dt1/rows",
+            None,
+            &rows.to_string(),
+        );
+        assert_eq!(status, 200);
+        mock
+    }
+
+    fn get(mock: &mut Mock, url: &str) -> Value {
+        let (status, body) = mock.handle("GET", url, None, "");
+        assert_eq!(status, 200, "GET {url}: {body:?}");
+        body.unwrap()
+    }
+
+    fn ids(page: &Value) -> Vec<String> {
+        page["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| id_string(&item["id"]))
+            .collect()
+    }
+
+    fn row_filter(condition: &str, value: &str) -> String {
+        let filter = json!({ "type": "and", "filters": [
+            { "columnName": "sku", "condition": condition, "value": value },
+        ] });
+        format!(
+            "/api/v1/data-tables/dt1/rows?filter={}",
+            urlencoding::encode(&filter.to_string())
+        )
+    }
+
+    #[test]
+    fn filters_workflows_by_active_and_tags() {
+        let mut mock = mock();
+        assert_eq!(
+            ids(&get(&mut mock, "/api/v1/workflows?active=false")),
+            ["w2", "w3"]
+        );
+        assert_eq!(
+            ids(&get(&mut mock, "/api/v1/workflows?tags=prod")),
+            ["w1", "w2"]
+        );
+        assert_eq!(
+            ids(&get(&mut mock, "/api/v1/workflows?tags=prod,billing")),
+            ["w2"]
+        );
+    }
+
+    #[test]
+    fn pages_with_limit_and_cursor() {
+        let mut mock = mock();
+        let first = get(&mut mock, "/api/v1/workflows?limit=2");
+        assert_eq!(ids(&first), ["w1", "w2"]);
+        let cursor = first["nextCursor"].as_str().unwrap();
+
+        let last = get(
+            &mut mock,
+            &format!("/api/v1/workflows?limit=2&cursor={cursor}"),
+        );
+        assert_eq!(ids(&last), ["w3"]);
+        assert_eq!(last["nextCursor"], Value::Null);
+
+        let (status, _) = mock.handle("GET", "/api/v1/workflows?cursor=bogus", None, "");
+        assert_eq!(status, 400);
+    }
+
+    #[test]
+    fn like_treats_only_percent_and_underscore_as_wildcards() {
+        let mut mock = mock();
+        let skus = |page: &Value| -> Vec<String> {
+            page["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|row| row["sku"].as_str().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(
+            skus(&get(&mut mock, &row_filter("like", "A_1"))),
+            ["A_1", "AB1"]
+        );
+        assert_eq!(skus(&get(&mut mock, &row_filter("like", "A\\_%"))), ["A_1"]);
+        assert_eq!(
+            skus(&get(&mut mock, &row_filter("like", "%\\%%"))),
+            ["50% off"]
+        );
+        assert!(skus(&get(&mut mock, &row_filter("like", "A*"))).is_empty());
+        assert_eq!(skus(&get(&mut mock, &row_filter("ilike", "ab_"))), ["AB1"]);
+    }
+}
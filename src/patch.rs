@@ -0,0 +1,239 @@
+//! `--merge-patch` and `--json-patch` on full-replacement updates: fetch
+//! the current resource, patch it locally and send the whole result.
+
+use crate::client::ApiContext;
+use crate::command_tree::{CommandTree, Operation};
+use crate::{edit, given_body_field};
+use anyhow::{Context, Result, anyhow};
+use clap::{Arg, ArgAction, ArgGroup, Command};
+use serde_json::{Map, Value};
+use std::fs;
+
+pub struct Patched {
+    /// The patched resource, reduced to the fields the update accepts.
+    pub body: Value,
+    /// The resource as fetched, before patching.
+    pub current: Value,
+    pub unchanged: bool,
+}
+
+/// Only PUT updates replace the whole resource; a PATCH already takes a
+/// partial body.
+pub fn supports(op: &Operation) -> bool {
+    op.method == "PUT" && edit::by_update_op(op.name).is_some()
+}
+
+pub fn add_patch_args(op_cmd: Command) -> Command {
+    op_cmd
+        .arg(
+            Arg::new("merge-patch")
+                .long("merge-patch")
+                .value_name("JSON|PATH")
+                .conflicts_with_all(["body", "body-file"])
+                .help("JSON Merge Patch (RFC 7386) to apply to the current resource"),
+        )
+        .arg(
+            Arg::new("json-patch")
+                .long("json-patch")
+                .value_name("JSON|PATH")
+                .conflicts_with_all(["body", "body-file"])
+                .help("JSON Patch (RFC 6902) to apply to the current resource"),
+        )
+        .group(ArgGroup::new("patch").args(["merge-patch", "json-patch"]))
+        .arg(
+            Arg::new("skip-unchanged")
+                .long("skip-unchanged")
+                .action(ArgAction::SetTrue)
+                .requires("patch")
+                .help("Skip the update when the patch changes nothing"),
+        )
+}
+
+/// The body for a patched update, or `None` when no patch flag was given.
+pub fn patched_body(
+    ctx: &ApiContext,
+    tree: &CommandTree,
+    op: &Operation,
+    matches: &clap::ArgMatches,
+) -> Result<Option<Patched>> {
+    if !supports(op) {
+        return Ok(None);
+    }
+    let merge = matches.get_one::<String>("merge-patch");
+    let json = matches.get_one::<String>("json-patch");
+    if merge.is_none() && json.is_none() {
+        return Ok(None);
+    }
+    if let Some(field) = given_body_field(op, matches) {
+        return Err(anyhow!(
+            "--{} cannot be combined with a patch; patch the field instead",
+            field.flag
+        ));
+    }
+
+    let editable = edit::by_update_op(op.name)
+        .ok_or_else(|| anyhow!("{} does not support patches", op.name))?;
+    let id_param = op
+        .params
+        .iter()
+        .find(|param| param.location == "path")
+        .ok_or_else(|| anyhow!("{} has no path parameter", op.name))?;
+    let id = matches
+        .get_one::<String>(id_param.name)
+        .ok_or_else(|| anyhow!("--{} required", id_param.flag))?;
+
+    let current = edit::fetch(ctx, tree, editable, id)?;
+    let mut patched = current.clone();
+    if let Some(patch) = merge {
+        merge_patch(&mut patched, &read_patch(patch)?);
+    }
+    if let Some(patch) = json {
+        json_patch(&mut patched, &read_patch(patch)?)?;
+    }
+    let body = edit::writable(op, &patched);
+    let unchanged = body == edit::writable(op, &current);
+    Ok(Some(Patched {
+        body,
+        current,
+        unchanged,
+    }))
+}
+
+/// Inline JSON when it looks like an object or array, otherwise a file.
+fn read_patch(arg: &str) -> Result<Value> {
+    let trimmed = arg.trim_start();
+    if trimmed.starts_with('{') || trimmed.starts_with('[') {
+        return serde_json::from_str(arg).context("invalid JSON patch");
+    }
+    let contents = fs::read_to_string(arg).with_context(|| format!("read patch file {arg}"))?;
+    serde_json::from_str(&contents).with_context(|| format!("invalid JSON in patch file {arg}"))
+}
+
+/// RFC 7386: objects merge recursively, `null` removes a key and anything
+/// else replaces the target.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge_patch(target.entry(key.as_str()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+/// RFC 6902, applied in order; the first failing operation aborts the
+/// patch.
+fn json_patch(doc: &mut Value, patch: &Value) -> Result<()> {
+    let ops = patch
+        .as_array()
+        .ok_or_else(|| anyhow!("JSON Patch must be an array of operations"))?;
+    for (index, op) in ops.iter().enumerate() {
+        apply_op(doc, op).map_err(|err| anyhow!("JSON Patch operation {index}: {err}"))?;
+    }
+    Ok(())
+}
+
+fn apply_op(doc: &mut Value, op: &Value) -> Result<()> {
+    let field = |name: &str| {
+        op.get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| anyhow!("missing \"{name}\""))
+    };
+    let value = || {
+        op.get("value")
+            .cloned()
+            .ok_or_else(|| anyhow!("missing \"value\""))
+    };
+    let path = field("path")?;
+    match field("op")? {
+        "add" => add(doc, path, value()?),
+        "remove" => remove(doc, path).map(drop),
+        "replace" => {
+            let target = doc
+                .pointer_mut(path)
+                .ok_or_else(|| anyhow!("no value at {path}"))?;
+            *target = value()?;
+            Ok(())
+        }
+        "move" => {
+            let moved = remove(doc, field("from")?)?;
+            add(doc, path, moved)
+        }
+        "copy" => {
+            let from = field("from")?;
+            let copied = doc
+                .pointer(from)
+                .cloned()
+                .ok_or_else(|| anyhow!("no value at {from}"))?;
+            add(doc, path, copied)
+        }
+        "test" => {
+            if doc.pointer(path) != Some(&value()?) {
+                return Err(anyhow!("test failed at {path}"));
+            }
+            Ok(())
+        }
+        other => Err(anyhow!("unknown op \"{other}\"")),
+    }
+}
+
+/// Splits a JSON pointer into its parent pointer and unescaped last token.
+fn split_pointer(path: &str) -> Result<(&str, String)> {
+    let (parent, token) = path
+        .rsplit_once('/')
+        .ok_or_else(|| anyhow!("invalid JSON pointer {path:?}"))?;
+    Ok((parent, token.replace("~1", "/").replace("~0", "~")))
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<()> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    let (parent, token) = split_pointer(path)?;
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(fields)) => {
+            fields.insert(token, value);
+        }
+        Some(Value::Array(items)) => {
+            let index = if token == "-" {
+                items.len()
+            } else {
+                array_index(&token, items.len() + 1)?
+            };
+            items.insert(index, value);
+        }
+        _ => return Err(anyhow!("no object or array at {parent:?}")),
+    }
+    Ok(())
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value> {
+    let (parent, token) = split_pointer(path)?;
+    let removed = match doc.pointer_mut(parent) {
+        Some(Value::Object(fields)) => fields.remove(&token),
+        Some(Value::Array(items)) => {
+            let index = array_index(&token, items.len())?;
+            Some(items.remove(index))
+        }
+        _ => None,
+    };
+    removed.ok_or_else(|| anyhow!("no value at {path}"))
+}
+
+fn array_index(token: &str, bound: usize) -> Result<usize> {
+    token
+        .parse::<usize>()
+        .ok()
+        .filter(|&index| index < bound)
+        .ok_or_else(|| anyhow!("array index {token} out of range"))
+}
@@ -0,0 +1,248 @@
+//! `n8n workflow search`: finds nodes across every workflow by type,
+//! parameter value or credential.
+
+use crate::client::{self, ApiContext};
+use crate::command_tree::CommandTree;
+use crate::export;
+use crate::{json_id, print_json, write_stdout_line};
+use anyhow::{Result, anyhow};
+use clap::{Arg, ArgAction, ArgGroup, Command};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+pub fn search_command() -> Command {
+    Command::new("search")
+        .about("Find nodes across all workflows by type, parameter or credential")
+        .arg(
+            Arg::new("node-type")
+                .long("node-type")
+                .value_name("TYPE")
+                .action(ArgAction::Append)
+                .help("Node type, e.g. n8n-nodes-base.slack; repeat to match any of several"),
+        )
+        .arg(
+            Arg::new("param-match")
+                .long("param-match")
+                .value_name("PATH~=REGEX")
+                .action(ArgAction::Append)
+                .help("Parameter whose value matches, as PATH~=REGEX or PATH=VALUE; PATH * matches any"),
+        )
+        .arg(
+            Arg::new("credential-id")
+                .long("credential-id")
+                .value_name("ID")
+                .help("Nodes using this credential"),
+        )
+        .group(
+            ArgGroup::new("criteria")
+                .args(["node-type", "param-match", "credential-id"])
+                .multiple(true)
+                .required(true),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .action(ArgAction::SetTrue)
+                .help("Emit machine-readable JSON"),
+        )
+}
+
+/// One `--param-match`: a dotted path under `parameters`, ignoring array
+/// indices, and how to test the value.
+struct ParamMatch {
+    path: Vec<String>,
+    test: ValueTest,
+}
+
+enum ValueTest {
+    Regex(Regex),
+    Equals(String),
+}
+
+#[derive(Serialize)]
+struct Hit {
+    workflow_id: String,
+    workflow_name: String,
+    node: String,
+    path: String,
+}
+
+pub fn handle_search(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let ctx = ApiContext::from_env()?;
+    let node_types: Vec<&String> = matches
+        .get_many::<String>("node-type")
+        .into_iter()
+        .flatten()
+        .collect();
+    let params = matches
+        .get_many::<String>("param-match")
+        .into_iter()
+        .flatten()
+        .map(|expr| parse_param_match(expr))
+        .collect::<Result<Vec<_>>>()?;
+    let credential = matches.get_one::<String>("credential-id");
+
+    let mut hits = Vec::new();
+    for workflow in full_workflows(&ctx, tree)? {
+        let id = export::workflow_id(&workflow)?;
+        let name = workflow["name"].as_str().unwrap_or_default();
+
+        for node in workflow["nodes"].as_array().into_iter().flatten() {
+            let mut paths = Vec::new();
+            if !node_types.is_empty() {
+                let node_type = node["type"].as_str().unwrap_or_default();
+                if !node_types.iter().any(|t| *t == node_type) {
+                    continue;
+                }
+                paths.push("type".to_string());
+            }
+            if let Some(credential) = credential {
+                let used: Vec<String> = node["credentials"]
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .filter(|(_, cred)| json_id(cred).is_some_and(|id| id == *credential))
+                    .map(|(kind, _)| format!("credentials.{kind}.id"))
+                    .collect();
+                if used.is_empty() {
+                    continue;
+                }
+                paths.extend(used);
+            }
+            if !params.is_empty() {
+                let mut scalars = Vec::new();
+                collect_scalars(
+                    "parameters",
+                    &mut Vec::new(),
+                    &node["parameters"],
+                    &mut scalars,
+                );
+                // Every --param-match must hit; report each path that did.
+                let mut matched: Vec<String> = Vec::new();
+                for param in &params {
+                    let found: Vec<&String> = scalars
+                        .iter()
+                        .filter(|(_, segments, value)| param.matches(segments, value))
+                        .map(|(path, _, _)| path)
+                        .collect();
+                    if found.is_empty() {
+                        matched.clear();
+                        break;
+                    }
+                    for path in found {
+                        if !matched.contains(path) {
+                            matched.push(path.clone());
+                        }
+                    }
+                }
+                if matched.is_empty() {
+                    continue;
+                }
+                paths.extend(matched);
+            }
+            for path in paths {
+                hits.push(Hit {
+                    workflow_id: id.clone(),
+                    workflow_name: name.to_string(),
+                    node: node["name"].as_str().unwrap_or_default().to_string(),
+                    path,
+                });
+            }
+        }
+    }
+
+    if matches.get_flag("json") {
+        return print_json(&serde_json::to_value(&hits)?, matches.get_flag("pretty"));
+    }
+    for hit in &hits {
+        write_stdout_line(&format!(
+            "{}\t{}\t{}\t{}",
+            hit.workflow_id, hit.workflow_name, hit.node, hit.path
+        ))?;
+    }
+    Ok(())
+}
+
+/// Every workflow with its nodes. Lists normally carry full workflows;
+/// any that do not are fetched one by one.
+pub fn full_workflows(ctx: &ApiContext, tree: &CommandTree) -> Result<Vec<Value>> {
+    client::fetch_all(ctx, tree, "workflow", "get-workflows", &[])?
+        .into_iter()
+        .map(|summary| {
+            if summary.get("nodes").is_some() {
+                return Ok(summary);
+            }
+            let args = ["--id".to_string(), export::workflow_id(&summary)?];
+            client::call_ok(ctx, tree, "workflow", "get-workflow", &args)
+        })
+        .collect()
+}
+
+fn parse_param_match(expr: &str) -> Result<ParamMatch> {
+    let (path, test) = if let Some((path, pattern)) = expr.split_once("~=") {
+        let regex = Regex::new(pattern).map_err(|err| anyhow!("--param-match {expr}: {err}"))?;
+        (path, ValueTest::Regex(regex))
+    } else if let Some((path, value)) = expr.split_once('=') {
+        (path, ValueTest::Equals(value.to_string()))
+    } else {
+        return Err(anyhow!(
+            "--param-match {expr}: expected PATH~=REGEX or PATH=VALUE"
+        ));
+    };
+    let path = path
+        .trim()
+        .trim_start_matches("parameters.")
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .map(str::to_string)
+        .collect();
+    Ok(ParamMatch { path, test })
+}
+
+impl ParamMatch {
+    /// An empty path or `*` matches every parameter; otherwise each segment
+    /// must match, with `*` standing for any one segment.
+    fn matches(&self, segments: &[String], value: &str) -> bool {
+        let any = self.path.is_empty() || self.path == ["*"];
+        let path_ok = any
+            || (self.path.len() == segments.len()
+                && self
+                    .path
+                    .iter()
+                    .zip(segments)
+                    .all(|(want, got)| want == "*" || want == got));
+        path_ok
+            && match &self.test {
+                ValueTest::Regex(regex) => regex.is_match(value),
+                ValueTest::Equals(expected) => value == expected,
+            }
+    }
+}
+
+/// Every scalar under `value` as (display path, key segments without array
+/// indices, value as text).
+fn collect_scalars(
+    path: &str,
+    segments: &mut Vec<String>,
+    value: &Value,
+    out: &mut Vec<(String, Vec<String>, String)>,
+) {
+    match value {
+        Value::Object(fields) => {
+            for (key, item) in fields {
+                segments.push(key.clone());
+                collect_scalars(&format!("{path}.{key}"), segments, item, out);
+                segments.pop();
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_scalars(&format!("{path}[{index}]"), segments, item, out);
+            }
+        }
+        Value::Null => {}
+        Value::String(text) => out.push((path.to_string(), segments.clone(), text.clone())),
+        other => out.push((path.to_string(), segments.clone(), other.to_string())),
+    }
+}
@@ -0,0 +1,207 @@
+use crate::command_tree::{CommandTree, InputField, Operation, SchemaDef};
+use crate::write_stdout_line;
+use anyhow::{Result, anyhow};
+use clap::{Arg, Command};
+use serde_json::{Map, Value, json};
+
+pub fn tools_command() -> Command {
+    Command::new("tools")
+        .about("Export operations as LLM tool definitions")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("export")
+                .about("Emit one tool definition per operation")
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_name("FORMAT")
+                        .value_parser(["openai", "anthropic", "mcp"])
+                        .default_value("openai")
+                        .help("Tool schema dialect"),
+                ),
+        )
+}
+
+pub fn handle_tools(tree: &CommandTree, matches: &clap::ArgMatches) -> Result<()> {
+    let Some(matches) = matches.subcommand_matches("export") else {
+        return Err(anyhow!("tools subcommand required"));
+    };
+    let format = matches
+        .get_one::<String>("format")
+        .map(String::as_str)
+        .unwrap_or("openai");
+
+    let mut tools = Vec::new();
+    for res in tree.resources {
+        for op in res.visible_ops() {
+            let tool = match format {
+                "anthropic" => json!({
+                    "name": tool_name(res.name, op.name),
+                    "description": tool_description(op),
+                    "input_schema": input_schema(op),
+                }),
+                "mcp" => mcp_tool(res.name, op),
+                _ => json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool_name(res.name, op.name),
+                        "description": tool_description(op),
+                        "parameters": input_schema(op),
+                    },
+                }),
+            };
+            tools.push(tool);
+        }
+    }
+
+    let out = if format == "mcp" {
+        json!({ "tools": tools })
+    } else {
+        Value::Array(tools)
+    };
+    write_stdout_line(&serde_json::to_string_pretty(&out)?)
+}
+
+/// Tool names must match `^[a-zA-Z0-9_-]{1,64}$` in every supported dialect.
+pub fn tool_name(resource: &str, op: &str) -> String {
+    format!("{resource}_{op}").replace('-', "_")
+}
+
+pub fn tool_description(op: &Operation) -> String {
+    let mut parts = Vec::new();
+    if let Some(summary) = op.summary.map(str::trim)
+        && !summary.is_empty()
+    {
+        parts.push(summary.trim_end_matches('.').to_string() + ".");
+    }
+    if let Some(description) = op.description.map(str::trim)
+        && !description.is_empty()
+        && Some(description) != op.summary.map(str::trim)
+    {
+        parts.push(description.split_whitespace().collect::<Vec<_>>().join(" "));
+    }
+    parts.push(format!("({} {})", op.method, op.path));
+    parts.join(" ")
+}
+
+pub fn mcp_tool(resource: &str, op: &Operation) -> Value {
+    let read_only = op.method == "GET";
+    json!({
+        "name": tool_name(resource, op.name),
+        "title": format!("{resource} {}", op.name),
+        "description": tool_description(op),
+        "inputSchema": input_schema(op),
+        "annotations": {
+            "readOnlyHint": read_only,
+            "destructiveHint": op.method == "DELETE",
+            "idempotentHint": matches!(op.method, "GET" | "PUT" | "DELETE"),
+            "openWorldHint": true,
+        },
+    })
+}
+
+/// JSON Schema for an operation's arguments: path and query params are
+/// top-level properties keyed by their API name, and the request body sits
+/// under `body` so its fields can never collide with a param.
+pub fn input_schema(op: &Operation) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    for param in op.params {
+        let mut schema = json_schema(&param.schema);
+        add_description(&mut schema, param.description);
+        properties.insert(param.name.to_string(), schema);
+        if param.required {
+            required.push(Value::String(param.name.to_string()));
+        }
+    }
+
+    if let Some(body) = &op.body {
+        let mut body_schema = if !body.variants.is_empty() {
+            let variants: Vec<Value> = body
+                .variants
+                .iter()
+                .map(|variant| {
+                    let mut schema = fields_schema(variant.input_fields);
+                    schema["title"] = Value::String(variant.name.to_string());
+                    schema
+                })
+                .collect();
+            json!({ "oneOf": variants })
+        } else if body.schema.kind == "object" && !body.input_fields.is_empty() {
+            fields_schema(body.input_fields)
+        } else {
+            json_schema(&body.schema)
+        };
+        add_description(
+            &mut body_schema,
+            Some(&format!("Request body ({})", body.content_type)),
+        );
+        properties.insert("body".to_string(), body_schema);
+        if body.required {
+            required.push(Value::String("body".to_string()));
+        }
+    }
+
+    let mut schema = json!({
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": false,
+    });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    schema
+}
+
+fn fields_schema(fields: &[InputField]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    for field in fields {
+        let mut schema = json_schema(&field.schema);
+        add_description(&mut schema, field.description);
+        properties.insert(field.name.to_string(), schema);
+        if field.required {
+            required.push(Value::String(field.name.to_string()));
+        }
+    }
+    let mut schema = json!({"type": "object", "properties": properties});
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    schema
+}
+
+fn json_schema(def: &SchemaDef) -> Value {
+    if let Some(variants) = def.variants {
+        return json!({ "anyOf": variants.iter().map(json_schema).collect::<Vec<_>>() });
+    }
+    let mut schema = match def.kind {
+        "array" => {
+            let items = def.item.map(json_schema).unwrap_or_else(|| json!({}));
+            json!({"type": "array", "items": items})
+        }
+        "string" | "integer" | "number" | "boolean" | "object" => json!({"type": def.kind}),
+        _ => json!({}),
+    };
+    if let Some(values) = &def.enum_values {
+        schema["enum"] = values.iter().map(|v| v.value()).collect();
+    }
+    if let Some(example) = &def.example {
+        schema["examples"] = json!([example.value()]);
+    }
+    schema
+}
+
+fn add_description(schema: &mut Value, description: Option<&str>) {
+    if let Some(description) = description.map(str::trim)
+        && !description.is_empty()
+        && let Some(obj) = schema.as_object_mut()
+    {
+        obj.insert(
+            "description".to_string(),
+            Value::String(description.split_whitespace().collect::<Vec<_>>().join(" ")),
+        );
+    }
+}
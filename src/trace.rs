@@ -0,0 +1,211 @@
+//! Debug output for HTTP traffic: `-v`/`-vv` log each exchange to stderr
+//! and `--har` collects them into an HTTP Archive written on exit.
+
+use anyhow::{Context, Result};
+use reqwest::header::HeaderMap;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+const REDACTED: &str = "[REDACTED]";
+const SECRET_HEADERS: &[&str] = &[
+    "x-n8n-api-key",
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+];
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+static HAR_ENTRIES: Mutex<Vec<Value>> = Mutex::new(Vec::new());
+
+struct Settings {
+    verbosity: u8,
+    har: Option<PathBuf>,
+}
+
+pub struct Request<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    pub headers: &'a HeaderMap,
+    pub body: Option<&'a [u8]>,
+    pub started: SystemTime,
+}
+
+pub enum Outcome<'a> {
+    Response {
+        status: u16,
+        reason: &'a str,
+        headers: &'a HeaderMap,
+        body: &'a str,
+    },
+    Failed(String),
+}
+
+pub fn init(verbosity: u8, har: Option<PathBuf>) {
+    let _ = SETTINGS.set(Settings { verbosity, har });
+}
+
+pub fn record(request: &Request, elapsed: Duration, outcome: &Outcome) {
+    let Some(settings) = SETTINGS.get() else {
+        return;
+    };
+    if settings.verbosity > 0 {
+        log(request, elapsed, outcome, settings.verbosity);
+    }
+    if settings.har.is_some()
+        && let Ok(mut entries) = HAR_ENTRIES.lock()
+    {
+        entries.push(har_entry(request, elapsed, outcome));
+    }
+}
+
+/// Writes the HAR file, if one was requested. Runs even when the command
+/// failed, since that is when the archive is most useful.
+pub fn finish() -> Result<()> {
+    let Some(path) = SETTINGS.get().and_then(|s| s.har.as_ref()) else {
+        return Ok(());
+    };
+    let entries = HAR_ENTRIES
+        .lock()
+        .map(|entries| entries.clone())
+        .unwrap_or_default();
+    let har = json!({
+        "log": {
+            "version": "1.2",
+            "creator": {"name": "n8n-cli", "version": env!("CARGO_PKG_VERSION")},
+            "entries": entries,
+        }
+    });
+    fs::write(path, serde_json::to_string_pretty(&har)?)
+        .with_context(|| format!("write {}", path.display()))
+}
+
+fn log(request: &Request, elapsed: Duration, outcome: &Outcome, verbosity: u8) {
+    eprintln!("> {} {}", request.method, request.url);
+    if verbosity > 1 {
+        for (name, value) in header_pairs(request.headers) {
+            eprintln!("> {name}: {value}");
+        }
+    }
+    if let Some(body) = request.body {
+        eprintln!("> body: {} bytes", body.len());
+    }
+
+    let millis = elapsed.as_millis();
+    match outcome {
+        Outcome::Response {
+            status,
+            reason,
+            headers,
+            body,
+        } => {
+            eprintln!("< {status} {reason} ({millis} ms, {} bytes)", body.len());
+            if verbosity > 1 {
+                for (name, value) in header_pairs(headers) {
+                    eprintln!("< {name}: {value}");
+                }
+            }
+        }
+        Outcome::Failed(err) => eprintln!("! {err} ({millis} ms)"),
+    }
+}
+
+fn header_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SECRET_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                value.to_str().unwrap_or("").to_string()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+fn har_headers(headers: &HeaderMap) -> Value {
+    header_pairs(headers)
+        .into_iter()
+        .map(|(name, value)| json!({"name": name, "value": value}))
+        .collect()
+}
+
+fn har_entry(request: &Request, elapsed: Duration, outcome: &Outcome) -> Value {
+    let millis = elapsed.as_secs_f64() * 1000.0;
+    let query: Vec<Value> = reqwest::Url::parse(request.url)
+        .map(|url| {
+            url.query_pairs()
+                .map(|(name, value)| json!({"name": name, "value": value}))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut har_request = json!({
+        "method": request.method,
+        "url": request.url,
+        "httpVersion": "HTTP/1.1",
+        "headers": har_headers(request.headers),
+        "queryString": query,
+        "cookies": [],
+        "headersSize": -1,
+        "bodySize": request.body.map_or(0, <[u8]>::len),
+    });
+    if let Some(body) = request.body {
+        har_request["postData"] = json!({
+            "mimeType": "application/json",
+            "text": String::from_utf8_lossy(body),
+        });
+    }
+
+    let response = match outcome {
+        Outcome::Response {
+            status,
+            reason,
+            headers,
+            body,
+        } => json!({
+            "status": status,
+            "statusText": reason,
+            "httpVersion": "HTTP/1.1",
+            "headers": har_headers(headers),
+            "cookies": [],
+            "content": {
+                "size": body.len(),
+                "mimeType": headers
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("application/octet-stream"),
+                "text": body,
+            },
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": body.len(),
+        }),
+        // Status 0 is how browsers record requests that never got a response.
+        Outcome::Failed(err) => json!({
+            "status": 0,
+            "statusText": "",
+            "httpVersion": "HTTP/1.1",
+            "headers": [],
+            "cookies": [],
+            "content": {"size": 0, "mimeType": "x-unknown"},
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": -1,
+            "_error": err,
+        }),
+    };
+
+    json!({
+        "startedDateTime": humantime::format_rfc3339_millis(request.started).to_string(),
+        "time": millis,
+        "request": har_request,
+        "response": response,
+        "cache": {},
+        "timings": {"send": 0, "wait": millis, "receive": 0},
+    })
+}
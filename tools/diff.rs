@@ -0,0 +1,366 @@
+//! Compares two command trees and classifies every difference by whether it
+//! breaks existing CLI invocations.
+
+use crate::spec::{BodyDef, CommandTree, InputField, Operation, ParamDef, SchemaDef};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Serialize)]
+pub struct Change {
+    pub breaking: bool,
+    pub kind: &'static str,
+    pub command: String,
+    pub target: String,
+    pub detail: String,
+}
+
+pub fn diff_trees(old: &CommandTree, new: &CommandTree) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let old_res: BTreeMap<_, _> = old.resources.iter().map(|r| (&r.name, r)).collect();
+    let new_res: BTreeMap<_, _> = new.resources.iter().map(|r| (&r.name, r)).collect();
+
+    for (name, res) in &old_res {
+        let Some(new_r) = new
+            .resources
+            .iter()
+            .find(|r| r.name == **name || r.aliases.contains(name))
+        else {
+            changes.push(change(true, "removed", name, "", "resource removed"));
+            continue;
+        };
+        if new_r.name != **name {
+            let detail = format!("renamed to {} (old name kept as alias)", new_r.name);
+            changes.push(change(false, "changed", name, "", &detail));
+        }
+        diff_aliases(
+            &mut changes,
+            name,
+            (&res.name, &res.aliases),
+            (&new_r.name, &new_r.aliases),
+        );
+
+        let old_ops: BTreeMap<_, _> = res.ops.iter().map(|o| (&o.name, o)).collect();
+        let new_ops: BTreeMap<_, _> = new_r.ops.iter().map(|o| (&o.name, o)).collect();
+        for (op_name, op) in &old_ops {
+            let command = format!("{name} {op_name}");
+            let Some(new_op) = new_r
+                .ops
+                .iter()
+                .find(|o| o.name == **op_name || o.aliases.contains(op_name))
+            else {
+                changes.push(change(true, "removed", &command, "", "command removed"));
+                continue;
+            };
+            if new_op.name != **op_name {
+                let detail = format!("renamed to {} (old name kept as alias)", new_op.name);
+                changes.push(change(false, "changed", &command, "", &detail));
+            }
+            diff_aliases(
+                &mut changes,
+                &command,
+                (&op.name, &op.aliases),
+                (&new_op.name, &new_op.aliases),
+            );
+            diff_op(&mut changes, &command, op, new_op);
+        }
+        for op in new_ops.values().filter(|o| {
+            !res.ops
+                .iter()
+                .any(|old| old.name == o.name || o.aliases.contains(&old.name))
+        }) {
+            let command = format!("{name} {}", op.name);
+            changes.push(change(false, "added", &command, "", "command added"));
+        }
+    }
+    for res in new_res.values().filter(|r| {
+        !old.resources
+            .iter()
+            .any(|old| old.name == r.name || r.aliases.contains(&old.name))
+    }) {
+        changes.push(change(false, "added", &res.name, "", "resource added"));
+    }
+
+    changes
+}
+
+/// An alias that disappears breaks whoever typed it, unless it became the
+/// primary name.
+fn diff_aliases(
+    changes: &mut Vec<Change>,
+    command: &str,
+    (old_name, old_aliases): (&str, &[String]),
+    (new_name, new_aliases): (&str, &[String]),
+) {
+    for alias in old_aliases
+        .iter()
+        .filter(|a| !new_aliases.contains(a) && *a != new_name)
+    {
+        let detail = format!("alias {alias} removed");
+        changes.push(change(true, "removed", command, "", &detail));
+    }
+    for alias in new_aliases
+        .iter()
+        .filter(|a| !old_aliases.contains(a) && *a != old_name)
+    {
+        let detail = format!("alias {alias} added");
+        changes.push(change(false, "added", command, "", &detail));
+    }
+}
+
+fn diff_op(changes: &mut Vec<Change>, command: &str, old: &Operation, new: &Operation) {
+    if old.method != new.method || old.path != new.path {
+        changes.push(change(
+            false,
+            "changed",
+            command,
+            "",
+            &format!(
+                "endpoint {} {} -> {} {}",
+                old.method, old.path, new.method, new.path
+            ),
+        ));
+    }
+
+    let old_params: BTreeMap<_, _> = old.params.iter().map(|p| (&p.flag, p)).collect();
+    let new_params: BTreeMap<_, _> = new.params.iter().map(|p| (&p.flag, p)).collect();
+    for (flag, param) in &old_params {
+        let target = format!("--{flag}");
+        match new_params.get(flag) {
+            Some(new_param) => diff_param(changes, command, &target, param, new_param),
+            None => changes.push(change(true, "removed", command, &target, "flag removed")),
+        }
+    }
+    for (flag, param) in new_params
+        .iter()
+        .filter(|(k, _)| !old_params.contains_key(*k))
+    {
+        let (breaking, detail) = added_detail(param.required, "flag");
+        changes.push(change(
+            breaking,
+            "added",
+            command,
+            &format!("--{flag}"),
+            &detail,
+        ));
+    }
+
+    diff_body(changes, command, old.body.as_ref(), new.body.as_ref());
+}
+
+fn diff_param(
+    changes: &mut Vec<Change>,
+    command: &str,
+    target: &str,
+    old: &ParamDef,
+    new: &ParamDef,
+) {
+    if old.location != new.location {
+        changes.push(change(
+            false,
+            "changed",
+            command,
+            target,
+            &format!("location {} -> {}", old.location, new.location),
+        ));
+    }
+    diff_required(changes, command, target, old.required, new.required);
+    diff_schema(changes, command, target, &old.schema, &new.schema);
+}
+
+fn diff_body(
+    changes: &mut Vec<Change>,
+    command: &str,
+    old: Option<&BodyDef>,
+    new: Option<&BodyDef>,
+) {
+    let target = "--body";
+    let (old, new) = match (old, new) {
+        (None, None) => return,
+        (Some(_), None) => {
+            changes.push(change(
+                true,
+                "removed",
+                command,
+                target,
+                "request body removed",
+            ));
+            return;
+        }
+        (None, Some(new)) => {
+            let (breaking, detail) = added_detail(new.required, "request body");
+            changes.push(change(breaking, "added", command, target, &detail));
+            return;
+        }
+        (Some(old), Some(new)) => (old, new),
+    };
+
+    if old.content_type != new.content_type {
+        changes.push(change(
+            false,
+            "changed",
+            command,
+            target,
+            &format!("content type {} -> {}", old.content_type, new.content_type),
+        ));
+    }
+    diff_required(changes, command, target, old.required, new.required);
+    diff_schema(changes, command, target, &old.schema, &new.schema);
+
+    let old_variants: BTreeMap<_, _> = old.variants.iter().map(|v| (&v.name, v)).collect();
+    for name in old_variants.keys() {
+        if !new.variants.iter().any(|v| v.name == **name) {
+            let detail = format!("body variant {name} removed");
+            changes.push(change(true, "removed", command, "--variant", &detail));
+        }
+    }
+    for variant in new
+        .variants
+        .iter()
+        .filter(|v| !old_variants.contains_key(&v.name))
+    {
+        let detail = format!("body variant {} added", variant.name);
+        changes.push(change(false, "added", command, "--variant", &detail));
+    }
+
+    let old_fields: BTreeMap<_, _> = old.input_fields.iter().map(|f| (&f.flag, f)).collect();
+    let new_fields: BTreeMap<_, _> = new.input_fields.iter().map(|f| (&f.flag, f)).collect();
+    for (flag, field) in &old_fields {
+        let target = format!("--{flag}");
+        match new_fields.get(flag) {
+            Some(new_field) => diff_field(changes, command, &target, field, new_field),
+            None => changes.push(change(
+                true,
+                "removed",
+                command,
+                &target,
+                "body field removed",
+            )),
+        }
+    }
+    for (flag, field) in new_fields
+        .iter()
+        .filter(|(k, _)| !old_fields.contains_key(*k))
+    {
+        let (breaking, detail) = added_detail(field.required, "body field");
+        changes.push(change(
+            breaking,
+            "added",
+            command,
+            &format!("--{flag}"),
+            &detail,
+        ));
+    }
+}
+
+fn diff_field(
+    changes: &mut Vec<Change>,
+    command: &str,
+    target: &str,
+    old: &InputField,
+    new: &InputField,
+) {
+    diff_required(changes, command, target, old.required, new.required);
+    diff_schema(changes, command, target, &old.schema, &new.schema);
+}
+
+fn diff_required(changes: &mut Vec<Change>, command: &str, target: &str, old: bool, new: bool) {
+    match (old, new) {
+        (false, true) => changes.push(change(true, "changed", command, target, "now required")),
+        (true, false) => changes.push(change(false, "changed", command, target, "now optional")),
+        _ => {}
+    }
+}
+
+fn diff_schema(
+    changes: &mut Vec<Change>,
+    command: &str,
+    target: &str,
+    old: &SchemaDef,
+    new: &SchemaDef,
+) {
+    let (old_kind, new_kind) = (kind_label(old), kind_label(new));
+    if old_kind != new_kind {
+        changes.push(change(
+            true,
+            "changed",
+            command,
+            target,
+            &format!("kind {old_kind} -> {new_kind}"),
+        ));
+    }
+
+    let old_enum = old.enum_values.as_deref().unwrap_or_default();
+    let new_enum = new.enum_values.as_deref().unwrap_or_default();
+    if new_enum.is_empty() {
+        return;
+    }
+    let removed: Vec<&Value> = old_enum.iter().filter(|v| !new_enum.contains(v)).collect();
+    let added: Vec<&Value> = new_enum.iter().filter(|v| !old_enum.contains(v)).collect();
+    if old_enum.is_empty() {
+        changes.push(change(
+            true,
+            "changed",
+            command,
+            target,
+            "values now restricted to an enum",
+        ));
+    } else if !removed.is_empty() {
+        changes.push(change(
+            true,
+            "changed",
+            command,
+            target,
+            &format!("enum values removed: {}", join_values(&removed)),
+        ));
+    }
+    if !old_enum.is_empty() && !added.is_empty() {
+        changes.push(change(
+            false,
+            "changed",
+            command,
+            target,
+            &format!("enum values added: {}", join_values(&added)),
+        ));
+    }
+}
+
+fn kind_label(schema: &SchemaDef) -> String {
+    if let Some(variants) = &schema.variants {
+        return variants
+            .iter()
+            .map(kind_label)
+            .collect::<Vec<_>>()
+            .join("|");
+    }
+    match &schema.item {
+        Some(item) => format!("{}<{}>", schema.kind, kind_label(item)),
+        None => schema.kind.clone(),
+    }
+}
+
+fn join_values(values: &[&Value]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn added_detail(required: bool, what: &str) -> (bool, String) {
+    if required {
+        (true, format!("required {what} added"))
+    } else {
+        (false, format!("optional {what} added"))
+    }
+}
+
+fn change(breaking: bool, kind: &'static str, command: &str, target: &str, detail: &str) -> Change {
+    Change {
+        breaking,
+        kind,
+        command: command.to_string(),
+        target: target.to_string(),
+        detail: detail.to_string(),
+    }
+}
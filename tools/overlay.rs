@@ -0,0 +1,334 @@
+//! Applies local corrections to the upstream OpenAPI document before it is
+//! converted. An overlay file is either an OpenAPI Overlay 1.0 document
+//! (`overlay:` + `actions:`) or a plain JSON merge-patch (RFC 7396).
+
+use anyhow::{Context, Result, bail};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Overlays applied by the build and by `gen-command-tree` when none are
+/// given explicitly, in file name order.
+pub const OVERLAY_DIR: &str = "overlays";
+
+#[derive(Debug, Clone)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+#[derive(Debug)]
+enum Selector {
+    Name(String),
+    Index(usize),
+    Wildcard,
+    Filter(Filter),
+}
+
+#[derive(Debug)]
+struct Filter {
+    key: String,
+    comparison: Option<(bool, Value)>,
+}
+
+pub fn default_overlays(dir: &Path) -> Result<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))? {
+        let path = entry?.path();
+        if matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("yaml" | "yml" | "json")
+        ) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Applies one overlay file, returning a warning for each action whose
+/// target matched nothing; the Overlay spec treats those as no-ops, which
+/// lets one overlay serve specs that lack some operations.
+pub fn apply_overlay_file(doc: &mut Value, path: &Path) -> Result<Vec<String>> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    let overlay: Value =
+        serde_yaml::from_str(&raw).with_context(|| format!("parse {}", path.display()))?;
+    apply_overlay(doc, &overlay).with_context(|| format!("apply overlay {}", path.display()))
+}
+
+pub fn apply_overlay(doc: &mut Value, overlay: &Value) -> Result<Vec<String>> {
+    if overlay.get("overlay").is_none() {
+        merge_patch(doc, overlay);
+        return Ok(Vec::new());
+    }
+
+    let actions = overlay
+        .get("actions")
+        .and_then(Value::as_array)
+        .context("overlay has no actions")?;
+    let mut warnings = Vec::new();
+    for (idx, action) in actions.iter().enumerate() {
+        let target = action
+            .get("target")
+            .and_then(Value::as_str)
+            .with_context(|| format!("action {idx}: target missing"))?;
+        let selectors = parse_path(target).with_context(|| format!("action {idx}: {target}"))?;
+        let mut matched = Vec::new();
+        select(doc, &selectors, Vec::new(), &mut matched);
+        if matched.is_empty() {
+            warnings.push(format!("action {idx}: target {target} matched nothing"));
+            continue;
+        }
+
+        if action.get("remove").and_then(Value::as_bool) == Some(true) {
+            // Later siblings first, so earlier array indices stay valid.
+            matched.sort_by_key(|path| std::cmp::Reverse(path.last().map(step_index)));
+            for path in &matched {
+                remove_at(doc, path);
+            }
+        } else if let Some(update) = action.get("update") {
+            for path in &matched {
+                if let Some(node) = node_mut(doc, path) {
+                    merge_update(node, update);
+                }
+            }
+        }
+    }
+    Ok(warnings)
+}
+
+/// RFC 7396: objects merge recursively, `null` deletes, anything else replaces.
+fn merge_patch(target: &mut Value, patch: &Value) {
+    let Some(patch) = patch.as_object() else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Some(target) = target.as_object_mut() else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// Overlay `update` semantics: objects merge recursively, arrays gain the
+/// update's items, and other values are replaced.
+fn merge_update(target: &mut Value, update: &Value) {
+    match (target, update) {
+        (Value::Object(target), Value::Object(update)) => {
+            for (key, value) in update {
+                match target.get_mut(key) {
+                    Some(existing) => merge_update(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (Value::Array(target), Value::Array(update)) => target.extend(update.iter().cloned()),
+        (Value::Array(target), update) => target.push(update.clone()),
+        (target, update) => *target = update.clone(),
+    }
+}
+
+fn step_index(step: &Step) -> usize {
+    match step {
+        Step::Index(idx) => *idx,
+        Step::Key(_) => 0,
+    }
+}
+
+fn node_mut<'a>(doc: &'a mut Value, path: &[Step]) -> Option<&'a mut Value> {
+    path.iter().try_fold(doc, |node, step| match step {
+        Step::Key(key) => node.get_mut(key.as_str()),
+        Step::Index(idx) => node.get_mut(*idx),
+    })
+}
+
+fn remove_at(doc: &mut Value, path: &[Step]) {
+    let Some((last, parent)) = path.split_last() else {
+        return;
+    };
+    match (node_mut(doc, parent), last) {
+        (Some(Value::Object(map)), Step::Key(key)) => {
+            map.remove(key);
+        }
+        (Some(Value::Array(items)), Step::Index(idx)) if *idx < items.len() => {
+            items.remove(*idx);
+        }
+        _ => {}
+    }
+}
+
+fn select(node: &Value, selectors: &[Selector], path: Vec<Step>, out: &mut Vec<Vec<Step>>) {
+    let Some((selector, rest)) = selectors.split_first() else {
+        out.push(path);
+        return;
+    };
+    let mut visit = |step: Step, child: &Value| {
+        let mut child_path = path.clone();
+        child_path.push(step);
+        select(child, rest, child_path, out);
+    };
+    match selector {
+        Selector::Name(name) => {
+            if let Some(child) = node.get(name.as_str()) {
+                visit(Step::Key(name.clone()), child);
+            }
+        }
+        Selector::Index(idx) => {
+            if let Some(child) = node.get(*idx) {
+                visit(Step::Index(*idx), child);
+            }
+        }
+        Selector::Wildcard | Selector::Filter(_) => {
+            let filter = match selector {
+                Selector::Filter(filter) => Some(filter),
+                _ => None,
+            };
+            let keep = |child: &Value| filter.is_none_or(|f| f.matches(child));
+            match node {
+                Value::Object(map) => {
+                    for (key, child) in map.iter().filter(|(_, child)| keep(child)) {
+                        visit(Step::Key(key.clone()), child);
+                    }
+                }
+                Value::Array(items) => {
+                    for (idx, child) in items.iter().enumerate().filter(|(_, c)| keep(c)) {
+                        visit(Step::Index(idx), child);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Filter {
+    fn matches(&self, node: &Value) -> bool {
+        let value = node.get(self.key.as_str());
+        match (&self.comparison, value) {
+            (None, value) => value.is_some(),
+            (Some((equal, expected)), Some(value)) => (value == expected) == *equal,
+            (Some((equal, _)), None) => !equal,
+        }
+    }
+}
+
+/// Parses the JSONPath subset overlays need: `$`, `.name`, `['name']`,
+/// `[0]`, `.*`, `[*]` and `[?(@.key == 'value')]` (also `!=` or a bare
+/// `@.key` existence test).
+fn parse_path(path: &str) -> Result<Vec<Selector>> {
+    let Some(mut rest) = path.trim().strip_prefix('$') else {
+        bail!("JSONPath must start with $");
+    };
+    let mut selectors = Vec::new();
+    while !rest.is_empty() {
+        if rest.starts_with("..") {
+            bail!("recursive descent is not supported");
+        }
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let name = &after[..end];
+            if name.is_empty() {
+                bail!("empty name segment");
+            }
+            selectors.push(if name == "*" {
+                Selector::Wildcard
+            } else {
+                Selector::Name(name.to_string())
+            });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = bracket_end(after).context("unterminated [")?;
+            selectors.push(parse_bracket(after[..end].trim())?);
+            rest = &after[end + 1..];
+        } else {
+            bail!("unexpected input at {rest}");
+        }
+    }
+    Ok(selectors)
+}
+
+/// Index of the `]` closing a bracket segment, skipping quoted strings and
+/// brackets nested inside filters.
+fn bracket_end(input: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut depth = 0;
+    for (idx, ch) in input.char_indices() {
+        match (quote, ch) {
+            (None, '\'' | '"') => quote = Some(ch),
+            (Some(q), c) if c == q => quote = None,
+            (None, '[') => depth += 1,
+            (None, ']') if depth == 0 => return Some(idx),
+            (None, ']') => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_bracket(inner: &str) -> Result<Selector> {
+    if inner == "*" {
+        return Ok(Selector::Wildcard);
+    }
+    if let Some(name) = unquote(inner) {
+        return Ok(Selector::Name(name));
+    }
+    if let Ok(idx) = inner.parse::<usize>() {
+        return Ok(Selector::Index(idx));
+    }
+    let Some(expr) = inner.strip_prefix('?') else {
+        bail!("unsupported selector [{inner}]");
+    };
+    let expr = expr.trim();
+    let expr = expr
+        .strip_prefix('(')
+        .and_then(|e| e.strip_suffix(')'))
+        .unwrap_or(expr)
+        .trim();
+    let Some(expr) = expr.strip_prefix('@') else {
+        bail!("filter must start with @");
+    };
+
+    let (key, comparison) = match expr.find("==").or_else(|| expr.find("!=")) {
+        Some(pos) => {
+            let equal = &expr[pos..pos + 2] == "==";
+            let literal = expr[pos + 2..].trim();
+            let value = match unquote(literal) {
+                Some(s) => Value::String(s),
+                None => serde_json::from_str(literal)
+                    .with_context(|| format!("invalid literal {literal}"))?,
+            };
+            (expr[..pos].trim(), Some((equal, value)))
+        }
+        None => (expr, None),
+    };
+    let key = if let Some(name) = key.strip_prefix('.') {
+        name.to_string()
+    } else if let Some(inner) = key.strip_prefix('[').and_then(|k| k.strip_suffix(']')) {
+        unquote(inner.trim()).with_context(|| format!("invalid filter key {key}"))?
+    } else {
+        bail!("invalid filter key {key}");
+    };
+    Ok(Selector::Filter(Filter { key, comparison }))
+}
+
+fn unquote(input: &str) -> Option<String> {
+    ['\'', '"'].iter().find_map(|q| {
+        input
+            .strip_prefix(*q)
+            .and_then(|s| s.strip_suffix(*q))
+            .map(str::to_string)
+    })
+}
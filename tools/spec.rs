@@ -0,0 +1,745 @@
+//! OpenAPI to command tree conversion, shared by the `gen-command-tree`
+//! binary and the build script that compiles the tree into the CLI.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommandTree {
+    pub version: String,
+    pub base_path: String,
+    pub resources: Vec<Resource>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Resource {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub ops: Vec<Operation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Operation {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub hidden: bool,
+    pub method: String,
+    pub path: String,
+    pub summary: Option<String>,
+    pub description: Option<String>,
+    pub params: Vec<ParamDef>,
+    pub body: Option<BodyDef>,
+    #[serde(default)]
+    pub response: Option<ResponseDef>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParamDef {
+    pub name: String,
+    pub flag: String,
+    pub location: String,
+    #[serde(default)]
+    pub style: String,
+    #[serde(default)]
+    pub explode: bool,
+    pub required: bool,
+    pub description: Option<String>,
+    pub schema: SchemaDef,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BodyDef {
+    pub required: bool,
+    pub content_type: String,
+    pub schema: SchemaDef,
+    pub input_fields: Vec<InputField>,
+    #[serde(default)]
+    pub discriminator: Option<String>,
+    #[serde(default)]
+    pub variants: Vec<BodyVariant>,
+}
+
+/// The documented success response: its status and whether it has a body.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResponseDef {
+    pub status: u16,
+    pub has_body: bool,
+}
+
+/// One `oneOf`/`anyOf` branch of a polymorphic request body.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BodyVariant {
+    pub name: String,
+    pub discriminator_value: Option<String>,
+    pub input_fields: Vec<InputField>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InputField {
+    pub name: String,
+    pub flag: String,
+    pub required: bool,
+    /// `readOnly` in the spec: returned by the server, rejected in requests.
+    #[serde(default)]
+    pub read_only: bool,
+    pub description: Option<String>,
+    pub schema: SchemaDef,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SchemaDef {
+    pub kind: String,
+    pub item: Option<Box<SchemaDef>>,
+    pub example: Option<Value>,
+    pub enum_values: Option<Vec<Value>>,
+    /// Every `oneOf`/`anyOf` branch; `kind` and the other fields describe
+    /// the first one.
+    #[serde(default)]
+    pub variants: Option<Vec<SchemaDef>>,
+}
+
+pub fn load_spec(path: &Path) -> Result<Value> {
+    let raw = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    serde_yaml::from_str(&raw).context("parse yaml")
+}
+
+pub fn command_tree_from_spec(doc: &Value) -> Result<CommandTree> {
+    let version = doc
+        .get("info")
+        .and_then(|v| v.get("version"))
+        .and_then(Value::as_str)
+        .unwrap_or("0")
+        .to_string();
+
+    let base_path = doc
+        .get("servers")
+        .and_then(Value::as_array)
+        .and_then(|servers| servers.first())
+        .and_then(|server| server.get("url"))
+        .and_then(Value::as_str)
+        .unwrap_or("/api/v1")
+        .to_string();
+
+    let paths = doc
+        .get("paths")
+        .and_then(Value::as_object)
+        .context("paths missing")?;
+
+    let mut resources: BTreeMap<String, (Vec<String>, Vec<Operation>)> = BTreeMap::new();
+
+    for (path, item) in paths {
+        let path_params = item
+            .get("parameters")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        for method in ["get", "post", "put", "patch", "delete"] {
+            let op = match item.get(method) {
+                Some(op) => op,
+                None => continue,
+            };
+
+            let op_obj = op.as_object().context("operation not object")?;
+            let tag = op_obj
+                .get("tags")
+                .and_then(Value::as_array)
+                .and_then(|tags| tags.first())
+                .and_then(Value::as_str)
+                .unwrap_or("default");
+            let (resource, resource_aliases) = cli_naming(tag_object(doc, tag), &to_kebab(tag));
+
+            let op_id = op_obj
+                .get("operationId")
+                .and_then(Value::as_str)
+                .or_else(|| op_obj.get("x-eov-operation-id").and_then(Value::as_str))
+                .unwrap_or("call");
+
+            let (name, aliases) = cli_naming(Some(op), &to_kebab(op_id));
+            let hidden = op_obj
+                .get("x-cli-hidden")
+                .and_then(Value::as_bool)
+                .unwrap_or(false);
+            let summary = op_obj
+                .get("summary")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let description = op_obj
+                .get("description")
+                .and_then(Value::as_str)
+                .map(str::to_string);
+
+            let op_params = op_obj
+                .get("parameters")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+
+            let params = merge_params(doc, &path_params, &op_params)?;
+            let body = parse_request_body(doc, op_obj.get("requestBody"))?;
+            let response = parse_response(doc, op_obj.get("responses"));
+
+            let op = Operation {
+                name,
+                aliases,
+                hidden,
+                method: method.to_uppercase(),
+                path: path.to_string(),
+                summary,
+                description,
+                params,
+                body,
+                response,
+            };
+
+            let entry = resources.entry(resource).or_default();
+            for alias in resource_aliases {
+                if !entry.0.contains(&alias) {
+                    entry.0.push(alias);
+                }
+            }
+            entry.1.push(op);
+        }
+    }
+
+    let mut out_resources = Vec::new();
+    for (name, (aliases, mut ops)) in resources {
+        ops.sort_by(|a, b| a.name.cmp(&b.name));
+        out_resources.push(Resource { name, aliases, ops });
+    }
+
+    Ok(CommandTree {
+        version,
+        base_path,
+        resources: out_resources,
+    })
+}
+
+fn tag_object<'a>(doc: &'a Value, tag: &str) -> Option<&'a Value> {
+    doc.get("tags")
+        .and_then(Value::as_array)?
+        .iter()
+        .find(|t| t.get("name").and_then(Value::as_str) == Some(tag))
+}
+
+/// Applies `x-cli-name` and `x-cli-aliases` to a generated command name.
+/// A renamed command keeps its generated name as an alias so existing
+/// invocations continue to work.
+fn cli_naming(item: Option<&Value>, generated: &str) -> (String, Vec<String>) {
+    let name = item
+        .and_then(|v| v.get("x-cli-name"))
+        .and_then(Value::as_str)
+        .unwrap_or(generated)
+        .to_string();
+    let mut aliases: Vec<String> = item
+        .and_then(|v| v.get("x-cli-aliases"))
+        .and_then(Value::as_array)
+        .map(|arr| {
+            arr.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    if name != generated {
+        aliases.push(generated.to_string());
+    }
+    let mut seen = BTreeSet::new();
+    aliases.retain(|alias| *alias != name && seen.insert(alias.clone()));
+    (name, aliases)
+}
+
+fn merge_params(doc: &Value, path_params: &[Value], op_params: &[Value]) -> Result<Vec<ParamDef>> {
+    let mut map: BTreeMap<(String, String), ParamDef> = BTreeMap::new();
+
+    for param in path_params {
+        if let Some(def) = parse_param(doc, param)? {
+            let key = (def.location.clone(), def.name.clone());
+            map.insert(key, def);
+        }
+    }
+
+    for param in op_params {
+        if let Some(def) = parse_param(doc, param)? {
+            let key = (def.location.clone(), def.name.clone());
+            map.insert(key, def);
+        }
+    }
+
+    Ok(map.into_values().collect())
+}
+
+fn parse_param(doc: &Value, param: &Value) -> Result<Option<ParamDef>> {
+    let param = resolve_ref(doc, param);
+    let name = param
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string();
+    if name.is_empty() {
+        return Ok(None);
+    }
+
+    let location = param
+        .get("in")
+        .and_then(Value::as_str)
+        .unwrap_or("query")
+        .to_string();
+    let required = param
+        .get("required")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    // OpenAPI defaults: form style for query and cookie params, simple
+    // elsewhere; only form explodes unless told otherwise.
+    let style = param
+        .get("style")
+        .and_then(Value::as_str)
+        .unwrap_or(match location.as_str() {
+            "query" | "cookie" => "form",
+            _ => "simple",
+        })
+        .to_string();
+    let explode = param
+        .get("explode")
+        .and_then(Value::as_bool)
+        .unwrap_or(style == "form");
+
+    let description = description_of(param);
+
+    let schema = param.get("schema").unwrap_or(&Value::Null);
+    let mut schema_def = schema_def(doc, schema);
+    if let Some(example) = param.get("example") {
+        schema_def.example = Some(example.clone());
+    }
+
+    Ok(Some(ParamDef {
+        name: name.clone(),
+        flag: to_kebab(&name),
+        location,
+        style,
+        explode,
+        required,
+        description,
+        schema: schema_def,
+    }))
+}
+
+fn parse_request_body(doc: &Value, request_body: Option<&Value>) -> Result<Option<BodyDef>> {
+    let Some(body) = request_body else {
+        return Ok(None);
+    };
+
+    let body = resolve_ref(doc, body);
+    let required = body
+        .get("required")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let content = body.get("content").and_then(Value::as_object);
+    let Some(content) = content else {
+        return Ok(None);
+    };
+
+    let (content_type, schema) = if let Some(json) = content.get("application/json") {
+        ("application/json".to_string(), json.get("schema"))
+    } else {
+        let first = content.iter().next();
+        match first {
+            Some((ct, item)) => (ct.clone(), item.get("schema")),
+            None => return Ok(None),
+        }
+    };
+
+    let schema = schema.unwrap_or(&Value::Null);
+    let schema_def = schema_def(doc, schema);
+    let (discriminator, variants) = body_variants(doc, schema);
+    let input_fields = if !variants.is_empty() {
+        merged_input_fields(&variants)
+    } else if schema_def.kind == "object" {
+        input_fields_from_schema(doc, schema)
+    } else {
+        Vec::new()
+    };
+
+    Ok(Some(BodyDef {
+        required,
+        content_type,
+        schema: schema_def,
+        input_fields,
+        discriminator,
+        variants,
+    }))
+}
+
+/// The lowest documented 2xx response.
+fn parse_response(doc: &Value, responses: Option<&Value>) -> Option<ResponseDef> {
+    let (status, response) = responses?
+        .as_object()?
+        .iter()
+        .filter_map(|(code, response)| Some((code.parse::<u16>().ok()?, response)))
+        .filter(|(status, _)| (200..300).contains(status))
+        .min_by_key(|(status, _)| *status)?;
+    let has_body = resolve_ref(doc, response)
+        .get("content")
+        .and_then(Value::as_object)
+        .is_some_and(|content| !content.is_empty());
+    Some(ResponseDef { status, has_body })
+}
+
+fn union_branches(schema: &Value) -> Option<&Vec<Value>> {
+    schema
+        .get("oneOf")
+        .or_else(|| schema.get("anyOf"))
+        .and_then(Value::as_array)
+        .filter(|branches| !branches.is_empty())
+}
+
+/// Splits a `oneOf`/`anyOf` body into named variants. Names come from the
+/// branch title, its discriminator mapping key or its `$ref` schema name.
+fn body_variants(doc: &Value, schema: &Value) -> (Option<String>, Vec<BodyVariant>) {
+    let schema = resolve_ref(doc, schema);
+    let Some(branches) = union_branches(schema) else {
+        return (None, Vec::new());
+    };
+    let discriminator = schema.get("discriminator");
+    let property = discriminator
+        .and_then(|d| d.get("propertyName"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let mapping = discriminator
+        .and_then(|d| d.get("mapping"))
+        .and_then(Value::as_object);
+
+    let variants = branches
+        .iter()
+        .enumerate()
+        .map(|(idx, branch)| {
+            let reference = branch.get("$ref").and_then(Value::as_str);
+            let ref_name = reference.and_then(|r| r.rsplit('/').next());
+            let resolved = resolve_ref(doc, branch);
+            let mapped = mapping.and_then(|m| {
+                m.iter()
+                    .find(|(_, target)| target.as_str() == reference && reference.is_some())
+                    .map(|(key, _)| key.as_str())
+            });
+            let discriminator_value = property.as_deref().and_then(|prop| {
+                let (properties, _) = object_parts(doc, branch);
+                let declared = properties.get(prop).and_then(|p| {
+                    let p = resolve_ref(doc, p);
+                    p.get("const").or_else(|| {
+                        p.get("enum")
+                            .and_then(Value::as_array)
+                            .filter(|values| values.len() == 1)
+                            .and_then(|values| values.first())
+                    })
+                });
+                mapped
+                    .map(str::to_string)
+                    .or_else(|| declared.and_then(Value::as_str).map(str::to_string))
+                    .or_else(|| ref_name.map(str::to_string))
+            });
+            let name = resolved
+                .get("title")
+                .and_then(Value::as_str)
+                .or(mapped)
+                .or(ref_name)
+                .or(discriminator_value.as_deref())
+                .map(to_kebab)
+                .unwrap_or_else(|| format!("variant-{}", idx + 1));
+            BodyVariant {
+                name,
+                discriminator_value,
+                input_fields: input_fields_from_schema(doc, branch),
+            }
+        })
+        .collect();
+    (property, variants)
+}
+
+/// Flags for a polymorphic body: every field of every variant, required
+/// only when all variants require it.
+fn merged_input_fields(variants: &[BodyVariant]) -> Vec<InputField> {
+    let mut merged: BTreeMap<&str, InputField> = BTreeMap::new();
+    for variant in variants {
+        for field in &variant.input_fields {
+            merged
+                .entry(field.name.as_str())
+                .or_insert_with(|| InputField {
+                    name: field.name.clone(),
+                    flag: field.flag.clone(),
+                    required: true,
+                    read_only: field.read_only,
+                    description: field.description.clone(),
+                    schema: field.schema.clone(),
+                });
+        }
+    }
+    for field in merged.values_mut() {
+        field.required = variants.iter().all(|v| {
+            v.input_fields
+                .iter()
+                .any(|f| f.name == field.name && f.required)
+        });
+    }
+    merged.into_values().collect()
+}
+
+/// Properties and required names of an object schema, merged across
+/// `allOf` parts.
+fn object_parts(doc: &Value, schema: &Value) -> (Map<String, Value>, BTreeSet<String>) {
+    let schema = resolve_ref(doc, schema);
+    let mut properties = Map::new();
+    let mut required = BTreeSet::new();
+
+    if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+        for part in parts {
+            let (part_props, part_required) = object_parts(doc, part);
+            properties.extend(part_props);
+            required.extend(part_required);
+        }
+    }
+    if let Some(props) = schema.get("properties").and_then(Value::as_object) {
+        properties.extend(props.clone());
+    }
+    if let Some(names) = schema.get("required").and_then(Value::as_array) {
+        required.extend(names.iter().filter_map(Value::as_str).map(str::to_string));
+    }
+    (properties, required)
+}
+
+fn input_fields_from_schema(doc: &Value, schema: &Value) -> Vec<InputField> {
+    let (properties, required) = object_parts(doc, schema);
+
+    let mut fields = Vec::new();
+    for (name, prop) in &properties {
+        let schema_def = schema_def(doc, prop);
+        let read_only = [prop, resolve_ref(doc, prop)]
+            .iter()
+            .any(|p| p.get("readOnly").and_then(Value::as_bool) == Some(true));
+        fields.push(InputField {
+            name: name.clone(),
+            flag: format!("input-{}", to_kebab(name)),
+            required: required.contains(name),
+            read_only,
+            description: description_of(resolve_ref(doc, prop)),
+            schema: schema_def,
+        });
+    }
+
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+    fields
+}
+
+fn schema_def(doc: &Value, schema: &Value) -> SchemaDef {
+    let schema = resolve_ref(doc, schema);
+
+    if let Some(all_of) = schema.get("allOf").and_then(Value::as_array)
+        && let Some(first) = all_of.first()
+    {
+        return schema_def(doc, first);
+    }
+
+    if let Some(branches) = union_branches(schema) {
+        let variants: Vec<SchemaDef> = branches.iter().map(|b| schema_def(doc, b)).collect();
+        let mut def = variants[0].clone();
+        def.variants = Some(variants);
+        if let Some(example) = schema.get("example") {
+            def.example = Some(example.clone());
+        }
+        return def;
+    }
+
+    let mut def = schema_kind(doc, schema);
+    def.example = schema.get("example").cloned();
+    def.enum_values = schema.get("enum").and_then(Value::as_array).cloned();
+    def
+}
+
+fn schema_kind(doc: &Value, schema: &Value) -> SchemaDef {
+    let type_value = schema.get("type").and_then(Value::as_str);
+    match type_value {
+        Some("object") => SchemaDef {
+            kind: "object".to_string(),
+            item: None,
+            example: None,
+            enum_values: None,
+            variants: None,
+        },
+        Some("array") => {
+            let item = schema
+                .get("items")
+                .map(|item| schema_def(doc, item))
+                .map(Box::new);
+            SchemaDef {
+                kind: "array".to_string(),
+                item,
+                example: None,
+                enum_values: None,
+                variants: None,
+            }
+        }
+        Some(kind) => SchemaDef {
+            kind: kind.to_string(),
+            item: None,
+            example: None,
+            enum_values: None,
+            variants: None,
+        },
+        None => {
+            if schema.get("properties").is_some() {
+                SchemaDef {
+                    kind: "object".to_string(),
+                    item: None,
+                    example: None,
+                    enum_values: None,
+                    variants: None,
+                }
+            } else if schema.get("items").is_some() {
+                let item = schema
+                    .get("items")
+                    .map(|item| schema_def(doc, item))
+                    .map(Box::new);
+                SchemaDef {
+                    kind: "array".to_string(),
+                    item,
+                    example: None,
+                    enum_values: None,
+                    variants: None,
+                }
+            } else {
+                SchemaDef {
+                    kind: "unknown".to_string(),
+                    item: None,
+                    example: None,
+                    enum_values: None,
+                    variants: None,
+                }
+            }
+        }
+    }
+}
+
+fn description_of(value: &Value) -> Option<String> {
+    value
+        .get("description")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+fn resolve_ref<'a>(doc: &'a Value, schema: &'a Value) -> &'a Value {
+    let Some(reference) = schema.get("$ref").and_then(Value::as_str) else {
+        return schema;
+    };
+
+    if !reference.starts_with("#/") {
+        return schema;
+    }
+
+    let mut current = doc;
+    for part in reference.trim_start_matches("#/").split('/') {
+        if let Some(next) = current.get(part) {
+            current = next;
+        } else {
+            return schema;
+        }
+    }
+
+    current
+}
+
+fn to_kebab(value: &str) -> String {
+    let mut out = String::new();
+    let mut prev_lower = false;
+
+    for ch in value.chars() {
+        if ch == '_' || ch == ' ' {
+            if !out.ends_with('-') {
+                out.push('-');
+            }
+            prev_lower = false;
+            continue;
+        }
+
+        if ch.is_ascii_uppercase() {
+            if prev_lower {
+                out.push('-');
+            }
+            out.push(ch.to_ascii_lowercase());
+            prev_lower = false;
+            continue;
+        }
+
+        out.push(ch);
+        prev_lower = ch.is_ascii_lowercase() || ch.is_ascii_digit();
+    }
+
+    out.trim_matches('-').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fixture(name: &str) -> CommandTree {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tools/fixtures")
+            .join(name);
+        command_tree_from_spec(&load_spec(&path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn discriminated_one_of_body_becomes_variants() {
+        let tree = fixture("polymorphic-body.yaml");
+        let op = &tree.resources[0].ops[0];
+        let body = op.body.as_ref().unwrap();
+
+        assert_eq!(body.discriminator.as_deref(), Some("channel"));
+        let variants: Vec<(&str, Option<&str>)> = body
+            .variants
+            .iter()
+            .map(|v| (v.name.as_str(), v.discriminator_value.as_deref()))
+            .collect();
+        assert_eq!(
+            variants,
+            [("email", Some("email")), ("webhook", Some("webhook"))]
+        );
+
+        let required: Vec<(&str, bool)> = body
+            .input_fields
+            .iter()
+            .map(|f| (f.name.as_str(), f.required))
+            .collect();
+        assert_eq!(
+            required,
+            [
+                ("channel", true),
+                ("secret", false),
+                ("to", false),
+                ("url", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn union_schema_keeps_its_own_example() {
+        let tree = fixture("polymorphic-body.yaml");
+        let schema = &tree.resources[0].ops[0].body.as_ref().unwrap().schema;
+
+        assert_eq!(
+            schema.example,
+            Some(json!({"channel": "email", "to": "ops@example.com"}))
+        );
+        assert_eq!(schema.variants.as_ref().map(Vec::len), Some(2));
+    }
+}